@@ -0,0 +1,239 @@
+//! Assembler for the mnemonic language produced by the disassembler.
+//!
+//! Each line holds an optional address check (`12:`), any number of
+//! labels (`loop:`) and either an instruction or a `data` directive:
+//!
+//! ```text
+//! start:  IN [value]
+//!         MUL [value], #2, [value]
+//!         OUT [value]
+//!         JT #1, #start
+//! value:  data 0
+//! ```
+//!
+//! Operands are `[addr]` for position, `#value` for immediate and
+//! `[rb+offset]` (or `[rb]` for offset 0) for relative mode, so `rb`
+//! can't be used as a label. Text after `;` is a comment.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use super::instruction::{Instruction, Mode, Opcode};
+
+/// Register name used in relative operands.
+const RELATIVE_BASE: &str = "rb";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AsmError {}
+
+#[derive(Debug)]
+enum Value {
+    Number(i128),
+    Label(String),
+}
+
+#[derive(Debug)]
+struct Operand {
+    mode: Mode,
+    value: Value,
+}
+
+#[derive(Debug)]
+enum Item {
+    Code(Opcode, Vec<Operand>),
+    Data(Vec<Value>),
+}
+
+/// Assembles `source` into a program ready for `IntcodeInterpreter::new`.
+pub fn assemble(source: &str) -> Result<Vec<i128>, AsmError> {
+    let mut labels: HashMap<String, usize> = HashMap::new();
+    let mut items: Vec<(usize, Item)> = Vec::new();
+    let mut address = 0usize;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |message: String| AsmError { line, message };
+
+        let mut text = raw_line.split(';').next().unwrap_or("").trim();
+        while let Some(colon) = text.find(':') {
+            let head = text[..colon].trim();
+            if head.chars().all(|c| c.is_ascii_digit()) && !head.is_empty() {
+                let expected = head.parse::<usize>().map_err(|e| error(e.to_string()))?;
+                if expected != address {
+                    return Err(error(format!(
+                        "address {} doesn't match assembled address {}",
+                        expected, address
+                    )));
+                }
+            } else if head == RELATIVE_BASE {
+                return Err(error(reserved_label()));
+            } else if is_label(head) {
+                if labels.insert(head.to_string(), address).is_some() {
+                    return Err(error(format!("label '{}' defined twice", head)));
+                }
+            } else {
+                return Err(error(format!("invalid label '{}'", head)));
+            }
+            text = text[colon + 1..].trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, rest) = match text.find(char::is_whitespace) {
+            Some(split) => (&text[..split], text[split..].trim()),
+            None => (text, ""),
+        };
+        let args: Vec<&str> = if rest.is_empty() {
+            Vec::new()
+        } else {
+            rest.split(',').map(str::trim).collect()
+        };
+
+        let item = if mnemonic.eq_ignore_ascii_case("data") {
+            if args.is_empty() {
+                return Err(error(String::from("data directive without values")));
+            }
+            let values = args
+                .iter()
+                .map(|a| parse_value(a))
+                .collect::<Result<Vec<Value>, String>>()
+                .map_err(error)?;
+            address += values.len();
+            Item::Data(values)
+        } else {
+            let opcode = Opcode::from_mnemonic(mnemonic)
+                .ok_or_else(|| error(format!("unknown mnemonic '{}'", mnemonic)))?;
+            if args.len() != opcode.arg_count() {
+                return Err(error(format!(
+                    "{} takes {} operands, found {}",
+                    opcode.mnemonic(),
+                    opcode.arg_count(),
+                    args.len()
+                )));
+            }
+            let operands = args
+                .iter()
+                .map(|a| parse_operand(a))
+                .collect::<Result<Vec<Operand>, String>>()
+                .map_err(error)?;
+            if let Some(arg) = opcode.write_arg() {
+                if operands[arg].mode == Mode::Immediate {
+                    return Err(error(format!(
+                        "{} can't write to an immediate operand",
+                        opcode.mnemonic()
+                    )));
+                }
+            }
+            address += opcode.arg_count() + 1;
+            Item::Code(opcode, operands)
+        };
+        items.push((line, item));
+    }
+
+    let mut program = Vec::with_capacity(address);
+    for (line, item) in items {
+        let resolve = |value: &Value| match value {
+            Value::Number(n) => Ok(*n),
+            Value::Label(name) => labels
+                .get(name)
+                .map(|&a| a as i128)
+                .ok_or_else(|| AsmError {
+                    line,
+                    message: format!("undefined label '{}'", name),
+                }),
+        };
+
+        match item {
+            Item::Code(opcode, operands) => {
+                let mut modes = [Mode::Position; 3];
+                for (mode, operand) in modes.iter_mut().zip(&operands) {
+                    *mode = operand.mode;
+                }
+                program.push(Instruction { opcode, modes }.encode());
+                for operand in &operands {
+                    program.push(resolve(&operand.value)?);
+                }
+            }
+            Item::Data(values) => {
+                for value in &values {
+                    program.push(resolve(value)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+fn reserved_label() -> String {
+    format!("'{}' is reserved for the relative base", RELATIVE_BASE)
+}
+
+fn is_label(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_value(text: &str) -> Result<Value, String> {
+    if text == RELATIVE_BASE {
+        return Err(reserved_label());
+    }
+    if is_label(text) {
+        return Ok(Value::Label(text.to_string()));
+    }
+    text.parse::<i128>()
+        .map(Value::Number)
+        .map_err(|_| format!("invalid value '{}'", text))
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    if let Some(value) = text.strip_prefix('#') {
+        return Ok(Operand {
+            mode: Mode::Immediate,
+            value: parse_value(value.trim())?,
+        });
+    }
+
+    let inner = text
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .map(str::trim)
+        .ok_or_else(|| format!("invalid operand '{}'", text))?;
+    match inner.strip_prefix("rb") {
+        Some(offset) if inner == RELATIVE_BASE || !is_label(inner) => {
+            let offset = offset.trim();
+            let value = match offset.strip_prefix('+') {
+                None if offset.is_empty() => Value::Number(0),
+                Some(positive) => parse_value(positive.trim())?,
+                None if offset.starts_with('-') => parse_value(&offset.replace(' ', ""))?,
+                None => return Err(format!("invalid relative operand '{}'", text)),
+            };
+            Ok(Operand {
+                mode: Mode::Relative,
+                value,
+            })
+        }
+        _ => Ok(Operand {
+            mode: Mode::Position,
+            value: parse_value(inner)?,
+        }),
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use super::error::AocError;
+use super::interpreter::{IntcodeError, IntcodeInterpreter};
+use super::solver::{Parameters, Puzzle};
+
+/// Executed instructions of an Intcode workload run repeatedly by
+/// `time_intcode`.
+#[derive(Clone, Debug)]
+pub struct StepRate {
+    pub name: String,
+    pub runs: u64,
+    pub steps: u64,
+    pub elapsed: Duration,
+}
+
+impl StepRate {
+    pub fn steps_per_second(&self) -> f64 {
+        self.steps as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Runs forks of `template` fed with `inputs` to completion until they
+/// took at least `min_time` in total.
+pub fn time_intcode(
+    name: &str,
+    template: &IntcodeInterpreter,
+    inputs: &[i128],
+    min_time: Duration,
+) -> Result<StepRate, IntcodeError> {
+    let mut rate = StepRate {
+        name: name.to_string(),
+        runs: 0,
+        steps: 0,
+        elapsed: Duration::from_secs(0),
+    };
+    while rate.elapsed < min_time {
+        let mut machine = template.clone();
+        for &input in inputs {
+            machine.set_input(input);
+        }
+
+        let start = Instant::now();
+        machine.execute()?;
+        rate.elapsed += start.elapsed();
+
+        rate.runs += 1;
+        rate.steps += machine.get_instruction_count();
+    }
+    Ok(rate)
+}
+
+/// Mean times of forking a machine and of a whole noun/verb search.
+#[derive(Clone, Debug)]
+pub struct SearchTiming {
+    pub searches: u64,
+    pub fork: Duration,
+    pub search: Duration,
+}
+
+/// Times the day 2 noun/verb search: every candidate forks `template`,
+/// patches addresses 1 and 2 and runs it. Forks are also timed on their
+/// own. Each measurement repeats for at least `min_time`.
+pub fn time_search(
+    template: &IntcodeInterpreter,
+    min_time: Duration,
+) -> Result<SearchTiming, IntcodeError> {
+    let mut forks = 0u32;
+    let mut elapsed = Duration::from_secs(0);
+    while elapsed < min_time {
+        let start = Instant::now();
+        for _ in 0..10_000 {
+            let machine = template.clone();
+            std::hint::black_box(&machine);
+        }
+        elapsed += start.elapsed();
+        forks += 10_000;
+    }
+    let fork = elapsed / forks;
+
+    let mut searches = 0u32;
+    let mut elapsed = Duration::from_secs(0);
+    while elapsed < min_time {
+        let start = Instant::now();
+        for noun in 0..100 {
+            for verb in 0..100 {
+                let mut machine = template.clone();
+                machine.set_memory(1, noun)?;
+                machine.set_memory(2, verb)?;
+                machine.execute()?;
+                std::hint::black_box(machine.read_memory(0));
+            }
+        }
+        elapsed += start.elapsed();
+        searches += 1;
+    }
+
+    Ok(SearchTiming {
+        searches: u64::from(searches),
+        fork,
+        search: elapsed / searches,
+    })
+}
+
+/// Writes `rates` and `search` as tables.
+pub fn write_intcode_table<W: Write>(
+    rates: &[StepRate],
+    search: &SearchTiming,
+    out: &mut W,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<12} {:>12} {:>12} {:>16}",
+        "workload", "runs", "steps", "steps/s"
+    )?;
+    for rate in rates {
+        writeln!(
+            out,
+            "{:<12} {:>12} {:>12} {:>16.0}",
+            rate.name,
+            rate.runs,
+            rate.steps,
+            rate.steps_per_second()
+        )?;
+    }
+
+    writeln!(
+        out,
+        "{:<12} {:>12} {:>14} {:>16}",
+        "workload", "searches", "fork (ns)", "search (ms)"
+    )?;
+    writeln!(
+        out,
+        "{:<12} {:>12} {:>14.1} {:>16.3}",
+        "day02 pt2",
+        search.searches,
+        search.fork.as_secs_f64() * 1e9,
+        search.search.as_secs_f64() * 1000.0
+    )?;
+    Ok(())
+}
+
+/// Phase of a puzzle solution timed by `time_solver`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Phase {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::Part1, Phase::Part2];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::Part1 => "part1",
+            Phase::Part2 => "part2",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Phase> {
+        Phase::ALL
+            .iter()
+            .copied()
+            .find(|phase| phase.name() == name)
+    }
+}
+
+/// Wall times of repeated runs of a phase.
+#[derive(Clone, Debug)]
+pub struct Timing {
+    pub day: u32,
+    pub phase: Phase,
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn new(day: u32, phase: Phase, mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Timing {
+            day,
+            phase,
+            runs: samples.len(),
+            min: samples[0],
+            median,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Parses `text` and solves both parts of the puzzle `runs` times,
+/// timing each phase separately.
+pub fn time_solver(
+    day: u32,
+    puzzle: &dyn Puzzle,
+    text: &str,
+    runs: usize,
+    parameters: &Parameters,
+) -> Result<Vec<Timing>, AocError> {
+    let mut samples = [Vec::new(), Vec::new(), Vec::new()];
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        let input = puzzle.parse(text)?;
+        samples[0].push(start.elapsed());
+
+        for part in 1..=2 {
+            let start = Instant::now();
+            std::hint::black_box(puzzle.solve(&input, part, parameters)?);
+            samples[part as usize].push(start.elapsed());
+        }
+    }
+
+    Ok(Phase::ALL
+        .iter()
+        .zip(samples)
+        .map(|(&phase, samples)| Timing::new(day, phase, samples))
+        .collect())
+}
+
+/// Median times of an earlier benchmark, read from its CSV report.
+#[derive(Clone, Debug, Default)]
+pub struct Baseline {
+    medians: HashMap<(u32, Phase), Duration>,
+}
+
+impl Baseline {
+    pub fn from_file(file_name: &str) -> Result<Self, AocError> {
+        Baseline::parse(&fs::read_to_string(file_name)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AocError> {
+        let mut medians = HashMap::new();
+        for (number, line) in text.lines().enumerate().skip(1) {
+            let fields: Vec<&str> = line.split(',').collect();
+            let timing = match fields[..] {
+                [day, phase, _, _, median, _] => day
+                    .parse()
+                    .ok()
+                    .zip(Phase::from_name(phase))
+                    .zip(median.parse().ok()),
+                _ => None,
+            };
+            let (key, median) = timing.ok_or_else(|| {
+                AocError::InvalidInput(format!("baseline line {}: '{}'", number + 1, line))
+            })?;
+            medians.insert(key, Duration::from_nanos(median));
+        }
+        Ok(Baseline { medians })
+    }
+
+    pub fn median(&self, day: u32, phase: Phase) -> Option<Duration> {
+        self.medians.get(&(day, phase)).copied()
+    }
+
+    /// Relative change of `timing`'s median against the baseline, in
+    /// percent.
+    pub fn change(&self, timing: &Timing) -> Option<f64> {
+        let baseline = self.median(timing.day, timing.phase)?.as_secs_f64();
+        if baseline == 0.0 {
+            return None;
+        }
+        Some((timing.median.as_secs_f64() / baseline - 1.0) * 100.0)
+    }
+}
+
+/// Writes `timings` as CSV, the format read by `Baseline`.
+pub fn write_csv<W: Write>(timings: &[Timing], out: &mut W) -> io::Result<()> {
+    writeln!(out, "day,phase,runs,min_ns,median_ns,max_ns")?;
+    for timing in timings {
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            timing.day,
+            timing.phase.name(),
+            timing.runs,
+            timing.min.as_nanos(),
+            timing.median.as_nanos(),
+            timing.max.as_nanos()
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes `timings` as a table. With a baseline, medians are compared to
+/// it and slowdowns above `threshold` percent are flagged.
+pub fn write_table<W: Write>(
+    timings: &[Timing],
+    baseline: Option<&Baseline>,
+    threshold: f64,
+    out: &mut W,
+) -> io::Result<()> {
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    write!(
+        out,
+        "{:<4} {:<6} {:>6} {:>12} {:>12} {:>12}",
+        "day", "phase", "runs", "min (ms)", "median (ms)", "max (ms)"
+    )?;
+    if baseline.is_some() {
+        write!(out, " {:>14} {:>8}", "baseline (ms)", "change")?;
+    }
+    writeln!(out)?;
+
+    for timing in timings {
+        write!(
+            out,
+            "{:<4} {:<6} {:>6} {:>12.3} {:>12.3} {:>12.3}",
+            format!("{:02}", timing.day),
+            timing.phase.name(),
+            timing.runs,
+            ms(timing.min),
+            ms(timing.median),
+            ms(timing.max)
+        )?;
+        if let Some(baseline) = baseline {
+            match (
+                baseline.median(timing.day, timing.phase),
+                baseline.change(timing),
+            ) {
+                (Some(median), Some(change)) => {
+                    write!(out, " {:>14.3} {:>+7.1}%", ms(median), change)?;
+                    if change > threshold {
+                        write!(out, " SLOWER")?;
+                    }
+                }
+                (Some(median), None) => write!(out, " {:>14.3} {:>8}", ms(median), "-")?,
+                (None, _) => write!(out, " {:>14} {:>8}", "-", "-")?,
+            }
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Timings whose median is more than `threshold` percent slower than the
+/// baseline.
+pub fn slowdowns<'a>(
+    timings: &'a [Timing],
+    baseline: &Baseline,
+    threshold: f64,
+) -> Vec<(&'a Timing, f64)> {
+    timings
+        .iter()
+        .filter_map(|timing| Some((timing, baseline.change(timing)?)))
+        .filter(|&(_, change)| change > threshold)
+        .collect()
+}
@@ -1,15 +1,41 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-pub fn solve(input_data: &str) {
-    let buffered = BufReader::new(File::open(input_data).unwrap());
-    let result = buffered
-        .lines()
-        .map(|l| l.unwrap())
-        .fold(0i32, |fuel_sum, mass| {
-            fuel_sum + (mass.parse::<i32>().unwrap() / 3) - 2
-        });
-    println!("Day 01.1: Sum of fuel requirements is {:} units.", result);
+use super::error::AocError;
+use super::input;
+use super::solver::{Parameters, Solver};
+
+pub struct Day01;
+
+impl Solver for Day01 {
+    /// Module masses.
+    type Input = Vec<i32>;
+    type Answer1 = i32;
+    type Answer2 = i32;
+
+    fn parse(&self, text: &str) -> Result<Vec<i32>, AocError> {
+        Ok(input::integer_lines(text)?)
+    }
+
+    /// Sum of fuel required by module masses.
+    fn part1(&self, masses: &Vec<i32>, _: &Parameters) -> Result<i32, AocError> {
+        Ok(masses
+            .iter()
+            .fold(0i32, |fuel_sum, mass| fuel_sum + (mass / 3) - 2))
+    }
+
+    /// Sum of fuel required by module masses, including fuel for the fuel.
+    fn part2(&self, masses: &Vec<i32>, _: &Parameters) -> Result<i32, AocError> {
+        Ok(masses.iter().fold(0i32, |fuel_sum, &mass| {
+            let fuel_req: i32 = FuelRequirement::new(mass).sum();
+            fuel_sum + fuel_req
+        }))
+    }
+
+    fn describe_part1(&self, fuel: &i32) -> String {
+        format!("Sum of fuel requirements is {} units.", fuel)
+    }
+
+    fn describe_part2(&self, fuel: &i32) -> String {
+        format!("Sum of fuel requirements is {} units.", fuel)
+    }
 }
 
 struct FuelRequirement {
@@ -18,7 +44,7 @@ struct FuelRequirement {
 
 impl FuelRequirement {
     fn new(mass: i32) -> FuelRequirement {
-        FuelRequirement { mass: mass }
+        FuelRequirement { mass }
     }
 }
 
@@ -35,19 +61,3 @@ impl Iterator for FuelRequirement {
         }
     }
 }
-
-pub fn solve_pt2(input_data: &str) {
-    let buffered = BufReader::new(File::open(input_data).unwrap());
-    let total_fuel_required = buffered
-        .lines()
-        .map(|l| l.unwrap().parse::<i32>().unwrap())
-        .fold(0i32, |fuel_sum, mass| {
-            let fuel_req: i32 = FuelRequirement::new(mass).sum();
-            fuel_sum + fuel_req
-        });
-
-    println!(
-        "Day 01.2: Sum of fuel requirements is {:} units.",
-        total_fuel_required
-    );
-}
@@ -1,61 +1,79 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fmt;
 
-use super::interpreter::IntcodeInterpreter;
+use super::error::AocError;
+use super::interpreter::{parse_program, IntcodeInterpreter};
+use super::solver::{Parameters, Solver};
 
-pub fn solve(input_file: &str) {
-    let mut intcode = read_input(input_file);
-    intcode[1] = 12;
-    intcode[2] = 2;
-
-    let mut computer = IntcodeInterpreter::new(&intcode);
-    computer.execute();
+/// Inputs of the gravity assist program; the puzzle answer is
+/// `100 * noun + verb`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct NounVerb {
+    pub noun: i32,
+    pub verb: i32,
+}
 
-    let result = computer.read_memory(0).unwrap();
-    assert!(result == 4462686);
-    println!("Day 02.1: intcode at 0: {:}", result)
+impl fmt::Display for NounVerb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", 100 * self.noun + self.verb)
+    }
 }
 
-pub fn solve_pt2(input_file: &str, expected_val: i32) {
-    let intcode = read_input(input_file);
+pub struct Day02;
 
-    let mut noun = 0i32;
-    let mut verb = 0i32;
-    while noun <= 99 && verb <= 99 {
-        let mut computer = IntcodeInterpreter::new(&intcode);
-        computer.set_memory(1, noun as i128);
-        computer.set_memory(2, verb as i128);
-        computer.execute();
+impl Solver for Day02 {
+    /// Gravity assist program.
+    type Input = Vec<i128>;
+    type Answer1 = i128;
+    type Answer2 = NounVerb;
 
-        if expected_val == computer.read_memory(0).unwrap() as i32 {
-            break;
-        }
-        verb += 1;
-        if verb > 99 {
-            verb = 0;
-            noun += 1;
+    fn parse(&self, text: &str) -> Result<Vec<i128>, AocError> {
+        Ok(parse_program(text)?)
+    }
+
+    /// Value left at address 0 after running the program restored to the
+    /// "1202 program alarm" state.
+    fn part1(&self, program: &Vec<i128>, _: &Parameters) -> Result<i128, AocError> {
+        let mut computer = IntcodeInterpreter::new(program)?;
+        computer.set_memory(1, 12)?;
+        computer.set_memory(2, 2)?;
+        computer.execute()?;
+
+        Ok(computer.read_memory(0).unwrap_or(0))
+    }
+
+    /// Noun and verb making the program leave `parameters.target` at
+    /// address 0.
+    fn part2(&self, program: &Vec<i128>, parameters: &Parameters) -> Result<NounVerb, AocError> {
+        let expected_val = parameters.target;
+        let template = IntcodeInterpreter::new(program)?;
+
+        for noun in 0..=99 {
+            for verb in 0..=99 {
+                let mut computer = template.clone();
+                computer.set_memory(1, i128::from(noun))?;
+                computer.set_memory(2, i128::from(verb))?;
+                computer.execute()?;
+
+                if computer.read_memory(0) == Some(i128::from(expected_val)) {
+                    return Ok(NounVerb { noun, verb });
+                }
+            }
         }
+
+        Err(AocError::NoAnswer(format!(
+            "no noun and verb produce {}",
+            expected_val
+        )))
     }
 
-    assert!(noun == 59);
-    assert!(verb == 36);
-    println!(
-        "Day 02.2: noun {:}, verb {:}, answer {:}",
-        noun,
-        verb,
-        100 * noun + verb
-    );
-}
+    fn describe_part1(&self, value: &i128) -> String {
+        format!("intcode at 0: {}", value)
+    }
 
-fn read_input(input_file: &str) -> Vec<i128> {
-    let mut buffered = BufReader::new(File::open(input_file).unwrap());
-    let mut input_data = String::new();
-    let r = buffered.read_line(&mut input_data);
-    if r.is_err() {
-        panic!("Failed to read input data!")
+    fn describe_part2(&self, inputs: &NounVerb) -> String {
+        format!(
+            "noun {}, verb {}, answer {}",
+            inputs.noun, inputs.verb, inputs
+        )
     }
-    input_data
-        .split(',')
-        .map(|c| c.parse::<i128>().unwrap())
-        .collect()
 }
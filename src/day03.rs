@@ -1,6 +1,8 @@
 use std::cmp::Ordering;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+
+use super::error::AocError;
+use super::input;
+use super::solver::{Parameters, Solver};
 
 #[derive(PartialEq, Debug)]
 struct Coord {
@@ -38,8 +40,8 @@ impl PartialEq for Point {
 
 impl Point {
     pub fn distance_to(&self, other: &Self) -> u32 {
-        let distance = (self.x - other.x).abs() as u32;
-        distance + (self.y - other.y).abs() as u32
+        let distance = (self.x - other.x).unsigned_abs();
+        distance + (self.y - other.y).unsigned_abs()
     }
 
     pub fn new(x: i32, y: i32) -> Self {
@@ -47,20 +49,59 @@ impl Point {
     }
 }
 
-pub fn solve(puzzle_input: &str) {
-    let (path_a, path_b) = read_input(puzzle_input);
+/// Paths of the two wires.
+pub struct Paths {
+    a: Vec<Coord>,
+    b: Vec<Coord>,
+}
+
+pub struct Day03;
+
+impl Solver for Day03 {
+    type Input = Paths;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, text: &str) -> Result<Paths, AocError> {
+        let mut paths = input::token_lines(text, ',', parse_step)?.into_iter();
+        match (paths.next(), paths.next(), paths.next()) {
+            (Some(a), Some(b), None) => Ok(Paths { a, b }),
+            _ => Err(AocError::InvalidInput(String::from(
+                "expected paths of two wires",
+            ))),
+        }
+    }
+
+    /// Manhattan distance from the central port to the closest intersection.
+    fn part1(&self, paths: &Paths, _: &Parameters) -> Result<u32, AocError> {
+        Ok(find_closest_intersections(paths)?.0)
+    }
+
+    /// Fewest combined steps the wires take to reach an intersection.
+    fn part2(&self, paths: &Paths, _: &Parameters) -> Result<u32, AocError> {
+        Ok(find_closest_intersections(paths)?.1)
+    }
+
+    fn describe_part1(&self, distance: &u32) -> String {
+        format!("distance to closest intersection is {}", distance)
+    }
+
+    fn describe_part2(&self, steps: &u32) -> String {
+        format!("{} steps required to reach intersection", steps)
+    }
+}
 
-    let wire_a = create_wire(path_a);
-    let wire_b = create_wire(path_b);
+fn find_closest_intersections(paths: &Paths) -> Result<(u32, u32), AocError> {
+    let wire_a = create_wire(&paths.a);
+    let wire_b = create_wire(&paths.b);
 
-    let mut distance = u32::max_value();
-    let mut step_count = u32::max_value();
+    let mut distance = u32::MAX;
+    let mut step_count = u32::MAX;
     let central_point = Point { x: 0, y: 0 };
     for line_a in wire_a.windows(2) {
         for line_b in wire_b.windows(2) {
-            let intersection = get_intersection_point(&line_a, &line_b);
-            if intersection.is_some() {
-                let intersection = intersection.unwrap();
+            let intersection = get_intersection_point(line_a, line_b);
+            if let Some(intersection) = intersection {
                 let dist = central_point.distance_to(&intersection);
                 if dist < distance {
                     distance = dist;
@@ -75,17 +116,16 @@ pub fn solve(puzzle_input: &str) {
         }
     }
 
-    println!("Day 03.1: distance to closest intersection is {}", distance);
-    println!(
-        "Day 03.2: {} steps required to reach intersection",
-        step_count
-    );
+    if distance == u32::MAX {
+        return Err(AocError::NoAnswer(String::from("wires don't cross")));
+    }
+    Ok((distance, step_count))
 }
 
 fn get_path_length_to(wire: &[Point], point: &Point) -> u32 {
     let mut step_count = 0u32;
     for line in wire.windows(2) {
-        if is_point_on_line(&point, line) {
+        if is_point_on_line(point, line) {
             step_count += point.distance_to(&line[0]);
             break;
         } else {
@@ -100,18 +140,17 @@ fn get_intersection_point(line_a: &[Point], line_b: &[Point]) -> Option<Point> {
     let horizontal_a = line_a[0].y == line_a[1].y;
     let horizontal_b = line_b[0].y == line_b[1].y;
     if horizontal_a != horizontal_b {
-        let point: Point;
-        if horizontal_b {
-            point = Point {
+        let point = if horizontal_b {
+            Point {
                 x: line_a[0].x,
                 y: line_b[0].y,
-            };
+            }
         } else {
-            point = Point {
+            Point {
                 x: line_b[0].x,
                 y: line_a[0].y,
-            };
-        }
+            }
+        };
         if point != Point::new(0, 0)
             && is_point_on_line(&point, line_a)
             && is_point_on_line(&point, line_b)
@@ -146,12 +185,12 @@ fn is_point_on_line(point: &Point, line: &[Point]) -> bool {
     x1 <= point.x && point.x <= x2 && y1 <= point.y && point.y <= y2
 }
 
-fn create_wire(path: Vec<Coord>) -> Vec<Point> {
+fn create_wire(path: &[Coord]) -> Vec<Point> {
     let mut cur_pos = Point { x: 0, y: 0 };
     let mut wire = vec![cur_pos.clone()];
 
     for coord in path {
-        update_pos(&coord, &mut cur_pos);
+        update_pos(coord, &mut cur_pos);
         wire.push(cur_pos.clone())
     }
 
@@ -168,24 +207,14 @@ fn update_pos(coord: &Coord, point: &mut Point) {
     }
 }
 
-fn read_input(input_file: &str) -> (Vec<Coord>, Vec<Coord>) {
-    let buffered = BufReader::new(File::open(input_file).unwrap());
-    let mut lines = buffered.lines();
-    let path_a = parse_path(&lines.next().unwrap().unwrap());
-    let path_b = parse_path(&lines.next().unwrap().unwrap());
-
-    (path_a, path_b)
-}
-
-fn parse_path(line: &String) -> Vec<Coord> {
-    let mut path = Vec::new();
-    for coord in line.split(',') {
-        let (row, pos) = coord.split_at(1);
-        path.push(Coord {
-            dir: row.chars().nth(0).unwrap(),
-            offset: pos.parse::<u16>().unwrap(),
-        })
+fn parse_step(step: &str) -> Result<Coord, String> {
+    let invalid = || format!("invalid path step '{}'", step);
+    let dir = step.chars().next().ok_or_else(invalid)?;
+    if !"LRUD".contains(dir) {
+        return Err(invalid());
     }
-
-    path
+    Ok(Coord {
+        dir,
+        offset: step[1..].parse::<u16>().map_err(|_| invalid())?,
+    })
 }
@@ -1,10 +1,78 @@
 use std::char;
 
-pub fn solve(pass_from: u32, pass_until: u32) {
+use super::error::AocError;
+use super::input;
+use super::solver::{Parameters, Solver};
+
+pub struct Day04;
+
+impl Solver for Day04 {
+    /// Inclusive range of passwords, given as `from-until`. A range in
+    /// `Parameters` takes precedence.
+    type Input = (u32, u32);
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, text: &str) -> Result<(u32, u32), AocError> {
+        let ranges = input::token_lines(text, '-', |password| {
+            password
+                .parse::<u32>()
+                .map_err(|_| format!("invalid password '{}'", password))
+        })?;
+        match ranges.as_slice() {
+            [range] if range.len() == 2 => check_range((range[0], range[1])),
+            _ => Err(AocError::InvalidInput(String::from(
+                "expected single password range as from-until",
+            ))),
+        }
+    }
+
+    /// Number of passwords in range with non-decreasing digits and a pair
+    /// of adjacent equal digits.
+    fn part1(&self, range: &(u32, u32), parameters: &Parameters) -> Result<u32, AocError> {
+        let (pass_from, pass_until) = selected_range(range, parameters)?;
+        Ok(count_passwords(pass_from, pass_until).0)
+    }
+
+    /// Number of passwords in range matching part 1 criteria that also
+    /// have a pair of equal digits not part of a larger group.
+    fn part2(&self, range: &(u32, u32), parameters: &Parameters) -> Result<u32, AocError> {
+        let (pass_from, pass_until) = selected_range(range, parameters)?;
+        Ok(count_passwords(pass_from, pass_until).1)
+    }
+
+    fn describe_part1(&self, count: &u32) -> String {
+        format!("matching password count: {}", count)
+    }
+
+    fn describe_part2(&self, count: &u32) -> String {
+        format!("matching password count: {}", count)
+    }
+}
+
+/// Range given in `parameters`, if any, or the one from the input.
+fn selected_range(range: &(u32, u32), parameters: &Parameters) -> Result<(u32, u32), AocError> {
+    match parameters.range {
+        Some(range) => check_range(range),
+        None => Ok(*range),
+    }
+}
+
+fn check_range((from, until): (u32, u32)) -> Result<(u32, u32), AocError> {
+    if from > until {
+        return Err(AocError::InvalidInput(format!(
+            "password range {}-{} is empty",
+            from, until
+        )));
+    }
+    Ok((from, until))
+}
+
+fn count_passwords(pass_from: u32, pass_until: u32) -> (u32, u32) {
     let mut password_count = 0u32;
     // let mut final_password = String::new();
     let mut password_count_2 = 0u32;
-    for password in pass_from..pass_until + 1 {
+    for password in pass_from..=pass_until {
         let pswd: Vec<char> = password.to_string().chars().collect();
         let is_increasing = pswd.windows(2).all(|x| x[0] <= x[1]);
         if is_increasing {
@@ -27,6 +95,5 @@ pub fn solve(pass_from: u32, pass_until: u32) {
         }
     }
 
-    println!("Day 04.1: matching password count: {:}", password_count);
-    println!("Day 04.2: matching password count: {}", password_count_2);
+    (password_count, password_count_2)
 }
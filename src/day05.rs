@@ -1,28 +1,49 @@
-use super::interpreter::IntcodeInterpreter;
+use super::error::AocError;
+use super::interpreter::{parse_program, IntcodeInterpreter};
+use super::solver::{Parameters, Solver};
 
-pub fn solve(input_file: &str) {
-    let mut interpreter = IntcodeInterpreter::from_file(input_file);
-    interpreter.set_input(1);
-    interpreter.execute();
+pub struct Day05;
 
-    let mut output = interpreter.get_output();
-    while output == Some(0) {
-        output = interpreter.get_output();
+impl Solver for Day05 {
+    /// Diagnostic program.
+    type Input = Vec<i128>;
+    type Answer1 = i128;
+    type Answer2 = i128;
+
+    fn parse(&self, text: &str) -> Result<Vec<i128>, AocError> {
+        Ok(parse_program(text)?)
+    }
+
+    /// Diagnostic code produced for the air conditioner unit, system ID 1.
+    fn part1(&self, program: &Vec<i128>, _: &Parameters) -> Result<i128, AocError> {
+        run_diagnostics(program, 1)
     }
-    assert!(output == Some(12234644));
-    println!("Day 05.1: Output is {:?}", output);
-}
 
-pub fn solve_pt2(input_file: &str) {
-    let mut interpreter = IntcodeInterpreter::from_file(input_file);
-    interpreter.set_input(5);
+    /// Diagnostic code produced for the thermal radiator controller,
+    /// system ID 5.
+    fn part2(&self, program: &Vec<i128>, _: &Parameters) -> Result<i128, AocError> {
+        run_diagnostics(program, 5)
+    }
+
+    fn describe_part1(&self, code: &i128) -> String {
+        format!("Output is {}", code)
+    }
+
+    fn describe_part2(&self, code: &i128) -> String {
+        format!("Output is {}", code)
+    }
+}
 
-    interpreter.execute();
+/// Runs diagnostic program and returns the first non-zero output value,
+/// the preceding zeros reporting passed tests.
+fn run_diagnostics(program: &[i128], system_id: i128) -> Result<i128, AocError> {
+    let mut interpreter = IntcodeInterpreter::new(program)?;
+    interpreter.set_input(system_id);
+    interpreter.execute()?;
 
     let mut output = interpreter.get_output();
     while output == Some(0) {
         output = interpreter.get_output();
     }
-    assert!(output == Some(3508186));
-    println!("Day 05.2: Output is {:?}", output);
+    output.ok_or_else(|| AocError::NoAnswer(String::from("no diagnostic code")))
 }
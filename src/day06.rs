@@ -1,7 +1,9 @@
 use std::collections::btree_map::{BTreeMap, Entry};
 use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+
+use super::error::AocError;
+use super::input;
+use super::solver::{Parameters, Solver};
 
 struct SpaceObject {
     orbits: Option<String>,
@@ -9,44 +11,90 @@ struct SpaceObject {
 }
 
 impl SpaceObject {
-    pub fn new(on_orbit: &String) -> Self {
+    pub fn new(on_orbit: &str) -> Self {
         SpaceObject {
             orbits: None,
-            on_orbit: vec![on_orbit.clone()],
+            on_orbit: vec![on_orbit.to_string()],
         }
     }
 
-    pub fn for_orbit(orbits: &String) -> Self {
+    pub fn for_orbit(orbits: &str) -> Self {
         SpaceObject {
-            orbits: Some(orbits.clone()),
+            orbits: Some(orbits.to_string()),
             on_orbit: Vec::new(),
         }
     }
 
-    pub fn add_on_orbit(&mut self, object_id: &String) {
-        self.on_orbit.push(object_id.clone());
+    pub fn add_on_orbit(&mut self, object_id: &str) {
+        self.on_orbit.push(object_id.to_string());
+    }
+}
+
+pub struct Day06;
+
+impl Solver for Day06 {
+    /// Pairs of object and object orbiting it.
+    type Input = Vec<(String, String)>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(&self, text: &str) -> Result<Vec<(String, String)>, AocError> {
+        Ok(input::pairs(text, ')')?
+            .into_iter()
+            .map(|(center, object)| (String::from(center), String::from(object)))
+            .collect())
+    }
+
+    /// Total number of direct and indirect orbits.
+    fn part1(&self, orbit_map: &Vec<(String, String)>, _: &Parameters) -> Result<u32, AocError> {
+        count_orbits(orbit_map)
+    }
+
+    /// Number of orbital transfers needed to get from the object YOU
+    /// orbit to the object SAN orbits.
+    fn part2(&self, orbit_map: &Vec<(String, String)>, _: &Parameters) -> Result<u32, AocError> {
+        count_transfers(orbit_map)
+    }
+
+    fn describe_part1(&self, orbit_count: &u32) -> String {
+        format!("Orbit count checksum is {}", orbit_count)
+    }
+
+    fn describe_part2(&self, transfer_count: &u32) -> String {
+        format!("Required transfer count: {}", transfer_count)
     }
 }
 
-pub fn solve(input_file: &str) {
-    let orbit_treemap = make_orbit_treemap(input_file);
+fn count_orbits(orbit_map: &[(String, String)]) -> Result<u32, AocError> {
+    let orbit_treemap = make_orbit_treemap(orbit_map)?;
+    if !orbit_treemap.contains_key("COM") {
+        return Err(AocError::InvalidInput(String::from("COM is missing")));
+    }
 
     let mut orbit_count = 0u32;
     let com_object_id = String::from("COM");
     let mut to_visit: VecDeque<&String> = VecDeque::new();
     to_visit.push_back(&com_object_id);
     let mut distances: BTreeMap<String, u32> = BTreeMap::new();
-    while to_visit.len() > 0 {
-        let object_id = to_visit.pop_front().unwrap();
-        let space_object = orbit_treemap.get(object_id).expect("UNKNOWN OBJECT");
+    while let Some(object_id) = to_visit.pop_front() {
+        if distances.contains_key(object_id) {
+            return Err(AocError::InvalidInput(format!(
+                "orbits form a cycle through {}",
+                object_id
+            )));
+        }
+        let space_object = get_object(&orbit_treemap, object_id)?;
 
         if object_id == &com_object_id {
             distances.insert(object_id.clone(), 0);
         } else {
-            assert!(space_object.orbits.is_some());
-            let com_dist = distances
-                .get(space_object.orbits.as_ref().unwrap())
-                .expect("NOT KNOWN DISTANCE")
+            let com_dist = space_object
+                .orbits
+                .as_ref()
+                .and_then(|parent| distances.get(parent))
+                .ok_or_else(|| {
+                    AocError::InvalidInput(format!("distance of {} to COM is unknown", object_id))
+                })?
                 + 1;
             orbit_count += com_dist;
             distances.insert(object_id.clone(), com_dist);
@@ -57,32 +105,36 @@ pub fn solve(input_file: &str) {
         }
     }
 
-    println!("Day 06.1: Orbit count checksum is {}", orbit_count);
+    if let Some(object_id) = orbit_treemap.keys().find(|&id| !distances.contains_key(id)) {
+        return Err(AocError::InvalidInput(format!(
+            "{} doesn't orbit COM",
+            object_id
+        )));
+    }
+    Ok(orbit_count)
 }
 
-pub fn solve_pt2(input_file: &str) {
-    let orbit_treemap = make_orbit_treemap(input_file);
+fn count_transfers(orbit_map: &[(String, String)]) -> Result<u32, AocError> {
+    let orbit_treemap = make_orbit_treemap(orbit_map)?;
 
-    let mut transfer_count = 0u32;
+    let mut transfer_count = None;
     let santa = String::from("SAN");
     let mut to_visit: VecDeque<String> = VecDeque::new();
     let parent = orbit_treemap
         .get("YOU")
-        .expect("UNKNOWN ME")
-        .orbits
-        .as_ref()
-        .expect("Parent missing!");
+        .and_then(|you| you.orbits.as_ref())
+        .ok_or_else(|| AocError::InvalidInput(String::from("YOU doesn't orbit anything")))?;
     to_visit.push_front(parent.clone());
     let mut distances: BTreeMap<String, u32> = BTreeMap::new();
     distances.insert(parent.clone(), 0);
-    while to_visit.len() > 0 {
-        let object_id = to_visit.pop_front().unwrap();
-
+    while let Some(object_id) = to_visit.pop_front() {
         // check if santa is on current object orbit
-        let object = orbit_treemap.get(&object_id).expect("UNKNOWN OBJECT");
-        let object_distance = *distances.get(&object_id).expect("Unknown distance!");
+        let object = get_object(&orbit_treemap, &object_id)?;
+        let object_distance = *distances.get(&object_id).ok_or_else(|| {
+            AocError::InvalidInput(format!("distance of {} to YOU is unknown", object_id))
+        })?;
         if object.on_orbit.iter().find(|&x| x == &santa).is_some() {
-            transfer_count = object_distance;
+            transfer_count = Some(object_distance);
             break;
         }
 
@@ -91,7 +143,9 @@ pub fn solve_pt2(input_file: &str) {
         }
 
         //add parent to search list
-        let parent_id = object.orbits.as_ref().expect("Missing parent");
+        let parent_id = object.orbits.as_ref().ok_or_else(|| {
+            AocError::InvalidInput(format!("{} doesn't orbit anything", object_id))
+        })?;
         process_path_node(
             parent_id,
             object_distance + 1,
@@ -105,7 +159,7 @@ pub fn solve_pt2(input_file: &str) {
         }
     }
 
-    println!("Day 06.2: Required transfer count: {}", transfer_count);
+    transfer_count.ok_or_else(|| AocError::NoAnswer(String::from("SAN is unreachable")))
 }
 
 fn process_path_node(
@@ -114,41 +168,48 @@ fn process_path_node(
     distances: &mut BTreeMap<String, u32>,
     to_visit: &mut VecDeque<String>,
 ) {
-    let child_dist = distances.get(node_id);
-    if child_dist.is_none() || child_dist.unwrap() > &distance {
+    if distances.get(node_id).is_none_or(|&known| known > distance) {
         *distances.entry(node_id.clone()).or_insert(0) = distance;
         to_visit.push_back(node_id.clone());
     }
 }
 
-fn make_orbit_treemap(input_file: &str) -> BTreeMap<String, SpaceObject> {
-    let orbit_map_raw = read_orbit_map(input_file);
+fn get_object<'a>(
+    orbit_treemap: &'a BTreeMap<String, SpaceObject>,
+    object_id: &str,
+) -> Result<&'a SpaceObject, AocError> {
+    orbit_treemap
+        .get(object_id)
+        .ok_or_else(|| AocError::InvalidInput(format!("unknown object {}", object_id)))
+}
 
+/// Builds map of objects, failing when an object orbits two others.
+fn make_orbit_treemap(
+    orbit_map: &[(String, String)],
+) -> Result<BTreeMap<String, SpaceObject>, AocError> {
     let mut orbit_treemap: BTreeMap<String, SpaceObject> = BTreeMap::new();
-    for (object, orbits) in orbit_map_raw {
+    for (object, orbits) in orbit_map {
         match orbit_treemap.entry(object.clone()) {
             Entry::Vacant(v) => {
-                v.insert(SpaceObject::new(&orbits));
+                v.insert(SpaceObject::new(orbits));
             }
-            Entry::Occupied(mut o) => o.get_mut().add_on_orbit(&orbits),
+            Entry::Occupied(mut o) => o.get_mut().add_on_orbit(orbits),
         }
 
         match orbit_treemap.entry(orbits.clone()) {
             Entry::Vacant(v) => {
-                v.insert(SpaceObject::for_orbit(&object));
+                v.insert(SpaceObject::for_orbit(object));
+            }
+            Entry::Occupied(mut o) => {
+                if let Some(parent) = &o.get().orbits {
+                    return Err(AocError::InvalidInput(format!(
+                        "{} orbits both {} and {}",
+                        orbits, parent, object
+                    )));
+                }
+                o.get_mut().orbits = Some(object.clone())
             }
-            Entry::Occupied(mut o) => o.get_mut().orbits = Some(object.clone()),
         }
     }
-    orbit_treemap
-}
-
-fn read_orbit_map(input_file: &str) -> Vec<(String, String)> {
-    let buffered = BufReader::new(File::open(input_file).unwrap());
-    buffered
-        .lines()
-        .map(|x| x.unwrap())
-        .map(|x| x.split(')').map(|z: &str| String::from(z)).collect())
-        .map(|x: Vec<String>| (x[0].clone(), x[1].clone()))
-        .collect()
+    Ok(orbit_treemap)
 }
@@ -1,121 +1,159 @@
-use super::interpreter::{ExecutionState, IntcodeInterpreter};
+use std::fmt;
+use std::panic;
+use std::thread;
 
-struct PhaseSettings {
-    settings: [i32; 5],
-    state: [usize; 6],
-    index: usize,
-}
+use super::error::AocError;
+use super::interpreter::{parse_program, IntcodeInterpreter};
+use super::network::{Network, NetworkState};
+use super::permutations::Permutations;
+use super::solver::{Parameters, Solver};
 
-impl PhaseSettings {
-    pub fn new(settings: [i32; 5]) -> Self {
-        PhaseSettings {
-            settings,
-            state: [0; 6],
-            index: 0,
-        }
-    }
+/// Phase setting of the amplifiers together with the thruster signal it
+/// produces; the puzzle answer is the signal.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct PhaseSetting {
+    pub signal: i128,
+    pub phases: Vec<i32>,
+}
 
-    fn swap_elements(&mut self, pos_a: usize, pos_b: usize) {
-        let tmp = self.settings[pos_a];
-        self.settings[pos_a] = self.settings[pos_b];
-        self.settings[pos_b] = tmp;
+impl fmt::Display for PhaseSetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.signal)
     }
 }
 
-impl Iterator for PhaseSettings {
-    type Item = [i32; 5];
+pub struct Day07;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.state[5] == 0 {
-            self.state[5] = 1;
-            return Some(self.settings.clone());
-        }
+impl Solver for Day07 {
+    /// Amplifier controller software.
+    type Input = Vec<i128>;
+    type Answer1 = PhaseSetting;
+    type Answer2 = PhaseSetting;
 
-        while self.index < self.settings.len() {
-            if self.state[self.index] < self.index {
-                if self.index % 2 == 0 {
-                    self.swap_elements(0, self.index);
-                } else {
-                    self.swap_elements(self.state[self.index], self.index);
-                }
+    fn parse(&self, text: &str) -> Result<Vec<i128>, AocError> {
+        Ok(parse_program(text)?)
+    }
 
-                self.state[self.index] += 1;
-                self.index = 0;
-                return Some(self.settings.clone());
-            } else {
-                self.state[self.index] = 0;
-                self.index += 1;
+    /// Phase setting giving the largest signal a chain of amplifiers can
+    /// send to the thrusters.
+    fn part1(
+        &self,
+        program: &Vec<i128>,
+        parameters: &Parameters,
+    ) -> Result<PhaseSetting, AocError> {
+        let interpreter = IntcodeInterpreter::new(program)?;
+
+        let settings = Permutations::heap(&[0, 1, 2, 3, 4]);
+        find_best_setting(settings, parameters.workers, |setting| {
+            let mut signal = 0;
+            for phase in setting {
+                let mut amplifier = interpreter.clone();
+                amplifier.set_input(i128::from(*phase));
+                amplifier.set_input(signal);
+                amplifier.execute()?;
+                signal = amplifier
+                    .get_output()
+                    .ok_or_else(|| AocError::NoAnswer(String::from("amplifier sent no signal")))?;
             }
-        }
-        None
+            Ok(signal)
+        })
     }
-}
 
-pub fn solve(input_file: &str) {
-    let interpreter = IntcodeInterpreter::from_file(input_file);
+    /// Phase setting giving the largest signal amplifiers connected in
+    /// a feedback loop can send to the thrusters.
+    fn part2(
+        &self,
+        program: &Vec<i128>,
+        parameters: &Parameters,
+    ) -> Result<PhaseSetting, AocError> {
+        let interpreter = IntcodeInterpreter::new(program)?;
 
-    let settings = PhaseSettings::new([0, 1, 2, 3, 4]);
-    let mut phase_setting = [0i32; 5];
-    let mut largest_signal = 0;
-    for setting in settings {
-        let mut signal = 0;
-        for phase in &setting {
-            let mut amplifier = interpreter.clone();
-            amplifier.set_input(*phase);
-            amplifier.set_input(signal);
-            amplifier.execute();
-            signal = amplifier.get_output().expect("Signal value");
-        }
+        let settings = Permutations::heap(&[5, 6, 7, 8, 9]);
+        find_best_setting(settings, parameters.workers, |setting| {
+            let mut amplifiers = Network::ring(vec![interpreter.clone(); setting.len()]);
+            for (index, phase) in setting.iter().enumerate() {
+                amplifiers.set_input(index, i128::from(*phase));
+            }
+            amplifiers.set_input(0, 0);
 
-        if signal > largest_signal {
-            largest_signal = signal;
-            phase_setting = setting;
-        }
+            let state = amplifiers.run_parallel()?;
+            if state != NetworkState::Finished {
+                return Err(AocError::NoAnswer(format!(
+                    "amplifiers {:?} stopped: {:?}",
+                    setting, state
+                )));
+            }
+            amplifiers
+                .last_output(setting.len() - 1)
+                .ok_or_else(|| AocError::NoAnswer(String::from("amplifiers sent no signal")))
+        })
     }
 
-    println!(
-        "Day 07.1: Largest output signal is {} for phase setting {:?}",
-        largest_signal, phase_setting
-    );
-}
-
-pub fn solve_pt2(input_file: &str) {
-    let interpreter = IntcodeInterpreter::from_file(input_file);
+    fn describe_part1(&self, setting: &PhaseSetting) -> String {
+        format!(
+            "Largest output signal is {} for phase setting {:?}",
+            setting.signal, setting.phases
+        )
+    }
 
-    let settings = PhaseSettings::new([5, 6, 7, 8, 9]);
-    let mut phase_setting = [0i32; 5];
-    let mut largest_signal = 0;
-    for setting in settings {
-        let mut signal = 0;
-        let mut amplifiers = [
-            interpreter.clone(),
-            interpreter.clone(),
-            interpreter.clone(),
-            interpreter.clone(),
-            interpreter.clone(),
-        ];
-        for index in 0..setting.len() {
-            amplifiers[index].set_input(setting[index]);
-        }
+    fn describe_part2(&self, setting: &PhaseSetting) -> String {
+        format!(
+            "Largest output signal with feedback is {} for phase setting {:?}",
+            setting.signal, setting.phases
+        )
+    }
+}
 
-        while !amplifiers
-            .iter()
-            .all(|x| *x.get_state() == ExecutionState::Finished)
-        {
-            for amp in &mut amplifiers {
-                amp.set_input(signal);
-                amp.execute();
-                signal = amp.get_output().expect("Signal value");
+/// Returns the setting for which `amplify` produces the largest signal,
+/// trying settings on `workers` threads. Signals are compared in the
+/// order settings are generated, so ties go to the first setting and
+/// the first failure is reported just like in a sequential search.
+fn find_best_setting<F>(
+    settings: Permutations<i32>,
+    workers: usize,
+    amplify: F,
+) -> Result<PhaseSetting, AocError>
+where
+    F: Fn(&[i32]) -> Result<i128, AocError> + Sync,
+{
+    let settings: Vec<Vec<i32>> = settings.collect();
+    let workers = workers.clamp(1, settings.len().max(1));
+    let mut signals: Vec<Option<Result<i128, AocError>>> = settings.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|worker| {
+                let settings = &settings;
+                let amplify = &amplify;
+                scope.spawn(move || {
+                    settings
+                        .iter()
+                        .enumerate()
+                        .skip(worker)
+                        .step_by(workers)
+                        .map(|(index, setting)| (index, amplify(setting)))
+                        .collect::<Vec<(usize, Result<i128, AocError>)>>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            let results = handle
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload));
+            for (index, signal) in results {
+                signals[index] = Some(signal);
             }
         }
-        if signal > largest_signal {
-            largest_signal = signal;
-            phase_setting = setting;
+    });
+
+    let mut best: Option<PhaseSetting> = None;
+    for (setting, signal) in settings.into_iter().zip(signals) {
+        let signal = signal.expect("Signal of every setting")?;
+        if signal > best.as_ref().map_or(0, |best| best.signal) {
+            best = Some(PhaseSetting {
+                signal,
+                phases: setting,
+            });
         }
     }
-
-    println!(
-        "Day 07.2: Largest output signal with feedback is {} for phase setting {:?}",
-        largest_signal, phase_setting
-    );
+    best.ok_or_else(|| AocError::NoAnswer(String::from("no setting produces a signal")))
 }
@@ -1,72 +1,118 @@
-use std::fs::File;
-use std::io::prelude::*;
+use super::error::AocError;
+use super::input;
+use super::solver::{Parameters, Solver};
 
 const IMAGE_WIDTH: usize = 25;
 const IMAGE_HEIGHT: usize = 6;
 const IMAGE_SIZE: usize = IMAGE_HEIGHT * IMAGE_WIDTH;
 
-struct SpaceImage {
+/// Layers of an image in the Space Image Format.
+pub struct SpaceImage {
     layers: Vec<[u8; IMAGE_SIZE]>,
 }
 
 impl SpaceImage {
-    pub fn read_from_file(file_name: &str) -> Self {
-        let mut data = File::open(file_name).expect("Input file missing!");
+    pub fn parse(text: &str) -> Result<Self, AocError> {
+        let data = input::digit_grid(text, IMAGE_SIZE)?;
+        if let Some(pixel) = data.iter().flatten().find(|&&x| x > 2) {
+            return Err(AocError::InvalidInput(format!(
+                "invalid pixel value {}",
+                pixel
+            )));
+        }
 
         let mut img = SpaceImage { layers: Vec::new() };
-        loop {
+        for chunk in &data {
             let mut layer = [0u8; IMAGE_SIZE];
-            let res = data.read(&mut layer).unwrap();
-            if res == 0 {
-                break;
-            }
+            layer.copy_from_slice(chunk);
             img.layers.push(layer);
         }
 
-        img
+        Ok(img)
     }
 }
 
-pub fn solve(input_data: &str) {
-    let image = SpaceImage::read_from_file(input_data);
+pub struct Day08;
+
+impl Solver for Day08 {
+    type Input = SpaceImage;
+    type Answer1 = usize;
+    type Answer2 = String;
+
+    fn parse(&self, text: &str) -> Result<SpaceImage, AocError> {
+        SpaceImage::parse(text)
+    }
+
+    /// Number of 1 digits multiplied by number of 2 digits on the layer
+    /// with the fewest 0 digits.
+    fn part1(&self, image: &SpaceImage, _: &Parameters) -> Result<usize, AocError> {
+        Ok(check_value(image))
+    }
+
+    /// Decoded image, one line of text per row: `X` for white pixels,
+    /// space for black ones and `?` where all layers are transparent.
+    fn part2(&self, image: &SpaceImage, _: &Parameters) -> Result<String, AocError> {
+        Ok(decode(image))
+    }
+
+    fn describe_part1(&self, check: &usize) -> String {
+        format!("Space image check value is {}", check)
+    }
+
+    fn describe_part2(&self, image: &String) -> String {
+        let mut message = String::from("Final image is");
+        for row in image.lines() {
+            message.push_str("\n\t");
+            message.push_str(row);
+        }
+        message
+    }
+}
 
-    let image_stats: Vec<(usize, usize)> =image.layers.iter().map(|&x| {
-        (
-            x.iter().filter(|&x| *x == 48).count(),
-            x.iter().filter(|&x| *x == 49).count() * x.iter().filter(|&x| *x == 50).count(),
-        )
-    }).collect();
-    let layer = image_stats.iter().min_by(|x, y| x.0.cmp(&y.0)).expect("Largest layer");
+fn check_value(image: &SpaceImage) -> usize {
+    let image_stats: Vec<(usize, usize)> = image
+        .layers
+        .iter()
+        .map(|&x| {
+            (
+                x.iter().filter(|&x| *x == 0).count(),
+                x.iter().filter(|&x| *x == 1).count() * x.iter().filter(|&x| *x == 2).count(),
+            )
+        })
+        .collect();
+    let layer = image_stats
+        .iter()
+        .min_by(|x, y| x.0.cmp(&y.0))
+        .expect("Largest layer");
 
-    println!("Day 08.1: Space image check value is {}", layer.1);
+    layer.1
 }
 
 fn sum_layers(l1: &[u8; IMAGE_SIZE], l2: &[u8; IMAGE_SIZE]) -> [u8; IMAGE_SIZE] {
-    let mut final_layer = [50u8; IMAGE_SIZE];
+    let mut final_layer = [2u8; IMAGE_SIZE];
     for i in 0..IMAGE_SIZE {
-        final_layer[i] = if l1[i] == 50 { l2[i] } else { l1[i] };
+        final_layer[i] = if l1[i] == 2 { l2[i] } else { l1[i] };
     }
 
     final_layer
 }
 
-pub fn solve_pt2(input_data: &str, print_result: bool) {
-    let image = SpaceImage::read_from_file(input_data);
-
-    let final_image = image.layers.iter().fold([50u8; IMAGE_SIZE], |acc, x| sum_layers(&acc, x));
-    println!("Day 08.2: Final image is");
-    if print_result {
-        for y in 0..IMAGE_HEIGHT {
-            print!("\t");
-            for x in 0..IMAGE_WIDTH {
-                match final_image[y * IMAGE_WIDTH + x] {
-                    48 => print!(" "),
-                    49 => print!("X"),
-                    50 => print!("?"),
-                    _ => panic!(format!("Unsupported value => {}", final_image[y*x]) )
-                }
+fn decode(image: &SpaceImage) -> String {
+    let final_image = image
+        .layers
+        .iter()
+        .fold([2u8; IMAGE_SIZE], |acc, x| sum_layers(&acc, x));
+    let mut rendered = String::new();
+    for y in 0..IMAGE_HEIGHT {
+        for x in 0..IMAGE_WIDTH {
+            match final_image[y * IMAGE_WIDTH + x] {
+                0 => rendered.push(' '),
+                1 => rendered.push('X'),
+                2 => rendered.push('?'),
+                _ => unreachable!("pixel values are checked by SpaceImage::parse"),
             }
-            println!("");
         }
+        rendered.push('\n');
     }
+    rendered
 }
@@ -0,0 +1,326 @@
+use std::io::{self, BufRead, Write};
+
+use super::disassembler;
+use super::interpreter::{BreakCause, ExecutionState, IntcodeInterpreter};
+
+const HELP: &str = "\
+commands:
+  step [n]            execute n instructions (default 1)
+  continue            run until halt, missing input or breakpoint
+  break <addr>        set breakpoint
+  clear <addr>        remove breakpoint
+  watch <addr>        stop after writes to address
+  unwatch <addr>      remove watchpoint
+  breaks              list breakpoints and watchpoints
+  mem <addr> [n]      show n memory words (default 1)
+  set <addr> <value>  patch memory
+  list [addr] [n]     disassemble n entries (default from pc, 5 entries)
+  input <v>...        queue input values
+  output              show and drain pending output
+  regs                show pc, relative base and state
+  save <file>         write machine snapshot
+  load <file>         restore machine snapshot
+  help                show this message
+  quit                end session";
+
+/// Upper bound of words shown by `mem` and entries shown by `list`.
+const MAX_SHOWN: usize = 4096;
+
+/// Command driven debugging session for a single `IntcodeInterpreter`.
+/// Commands are read line by line so a session can be replayed from a
+/// script; lines starting with `#` are ignored.
+pub struct Debugger {
+    machine: IntcodeInterpreter,
+}
+
+impl Debugger {
+    pub fn new(machine: IntcodeInterpreter) -> Self {
+        Debugger { machine }
+    }
+
+    /// Reads commands from `commands` until `quit` or end of input. With
+    /// `echo` set every command is written to `out` before its result,
+    /// which keeps transcripts of scripted sessions readable.
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        commands: R,
+        out: &mut W,
+        echo: bool,
+    ) -> io::Result<()> {
+        if !echo {
+            write!(out, "(icd) ")?;
+            out.flush()?;
+        }
+        for line in commands.lines() {
+            let line = line?;
+            let command = line.trim();
+            if echo && !command.is_empty() {
+                writeln!(out, "(icd) {}", command)?;
+            }
+            if !command.is_empty() && !command.starts_with('#') && !self.command(command, out)? {
+                return Ok(());
+            }
+            if !echo {
+                write!(out, "(icd) ")?;
+                out.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes a single command. Returns `false` when the session should end.
+    pub fn command<W: Write>(&mut self, command: &str, out: &mut W) -> io::Result<bool> {
+        let mut words = command.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let args: Vec<&str> = words.collect();
+
+        let result = match name {
+            "step" | "s" => self.step(&args, out),
+            "continue" | "c" => self.continue_execution(out),
+            "break" | "b" => parse_address(args.first()).map(|a| {
+                self.machine.add_breakpoint(a);
+                format!("breakpoint at {}", a)
+            }),
+            "clear" => parse_address(args.first()).map(|a| {
+                if self.machine.remove_breakpoint(a) {
+                    format!("breakpoint at {} removed", a)
+                } else {
+                    format!("no breakpoint at {}", a)
+                }
+            }),
+            "watch" | "w" => parse_address(args.first()).map(|a| {
+                self.machine.add_watchpoint(a);
+                format!("watchpoint at {}", a)
+            }),
+            "unwatch" => parse_address(args.first()).map(|a| {
+                if self.machine.remove_watchpoint(a) {
+                    format!("watchpoint at {} removed", a)
+                } else {
+                    format!("no watchpoint at {}", a)
+                }
+            }),
+            "breaks" => Ok(self.breaks()),
+            "mem" | "x" => self.memory(&args),
+            "set" => self.patch(&args),
+            "list" | "l" => self.list(&args),
+            "input" | "i" => args
+                .iter()
+                .map(|a| parse_value(a))
+                .collect::<Result<Vec<i128>, String>>()
+                .map(|values| {
+                    for value in &values {
+                        self.machine.set_input(*value);
+                    }
+                    format!("queued {} input value(s)", values.len())
+                }),
+            "output" | "o" => Ok(self.output()),
+            "regs" | "r" => Ok(self.registers()),
+            "save" => match args.first() {
+                Some(file) => self
+                    .machine
+                    .save_snapshot_file(file)
+                    .map(|_| format!("snapshot saved to {}", file))
+                    .map_err(|e| e.to_string()),
+                None => Err(String::from("missing snapshot file")),
+            },
+            "load" => match args.first() {
+                Some(file) => IntcodeInterpreter::load_snapshot_file(file)
+                    .map(|machine| {
+                        self.machine = machine;
+                        format!("snapshot loaded from {}", file)
+                    })
+                    .map_err(|e| e.to_string()),
+                None => Err(String::from("missing snapshot file")),
+            },
+            "help" | "h" => Ok(String::from(HELP)),
+            "quit" | "q" => return Ok(false),
+            _ => Err(format!("unknown command '{}', try 'help'", name)),
+        };
+
+        match result {
+            Ok(message) => {
+                if !message.is_empty() {
+                    writeln!(out, "{}", message)?
+                }
+            }
+            Err(message) => writeln!(out, "error: {}", message)?,
+        }
+        Ok(true)
+    }
+
+    fn step<W: Write>(&mut self, args: &[&str], out: &mut W) -> Result<String, String> {
+        let count = parse_count(args.first(), 1)?;
+
+        for _ in 0..count {
+            let listing = self.current_instruction();
+            let step = self.machine.step().map_err(|e| e.to_string())?;
+            let mut line = listing;
+            if let Some((address, value)) = step.write {
+                line += &format!("    ; [{}] <- {}", address, value);
+            }
+            if step.state == ExecutionState::Suspended {
+                line += "    ; waiting for input";
+            }
+            writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+            if step.state != ExecutionState::Working {
+                return Ok(format!("state: {}", describe_state(&step.state)));
+            }
+        }
+        Ok(String::new())
+    }
+
+    fn continue_execution<W: Write>(&mut self, out: &mut W) -> Result<String, String> {
+        let state = self.machine.execute().map_err(|e| e.to_string())?;
+        if let ExecutionState::Breakpoint(BreakCause::Address(_)) = state {
+            writeln!(out, "{}", self.current_instruction()).map_err(|e| e.to_string())?;
+        }
+        Ok(format!("state: {}", describe_state(&state)))
+    }
+
+    fn breaks(&self) -> String {
+        let breakpoints: Vec<String> = self
+            .machine
+            .get_breakpoints()
+            .map(usize::to_string)
+            .collect();
+        let watchpoints: Vec<String> = self
+            .machine
+            .get_watchpoints()
+            .map(usize::to_string)
+            .collect();
+        format!(
+            "breakpoints: [{}]\nwatchpoints: [{}]",
+            breakpoints.join(", "),
+            watchpoints.join(", ")
+        )
+    }
+
+    fn memory(&self, args: &[&str]) -> Result<String, String> {
+        let address = parse_address(args.first())?;
+        let count = parse_shown_count(args.get(1), 1)?;
+        let words = self.read_words(address, count)?;
+        let words: Vec<String> = words.iter().map(i128::to_string).collect();
+        Ok(format!("{:>5}: {}", address, words.join(", ")))
+    }
+
+    fn patch(&mut self, args: &[&str]) -> Result<String, String> {
+        let address = parse_address(args.first())?;
+        let value = parse_value(args.get(1).ok_or("missing value")?)?;
+        self.machine
+            .set_memory(address, value)
+            .map_err(|e| e.to_string())?;
+        Ok(format!("[{}] <- {}", address, value))
+    }
+
+    fn list(&self, args: &[&str]) -> Result<String, String> {
+        let address = match args.first() {
+            Some(_) => parse_address(args.first())?,
+            None => self.machine.get_instr_ptr(),
+        };
+        let count = parse_shown_count(args.get(1), 5)?;
+
+        // longest instruction takes 4 words
+        let words = self.read_words(address, count.saturating_mul(4))?;
+        let lines: Vec<String> = disassembler::disassemble_at(&words, address)
+            .iter()
+            .take(count)
+            .map(|line| self.mark_current(line.to_string(), line))
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    fn output(&mut self) -> String {
+        let mut values = Vec::new();
+        while let Some(value) = self.machine.get_output() {
+            values.push(value.to_string());
+        }
+        format!("output: [{}]", values.join(", "))
+    }
+
+    fn registers(&self) -> String {
+        format!(
+            "pc: {}, rb: {}, steps: {}, state: {}",
+            self.machine.get_instr_ptr(),
+            self.machine.get_relative_base(),
+            self.machine.get_instruction_count(),
+            describe_state(self.machine.get_state())
+        )
+    }
+
+    fn read_words(&self, address: usize, count: usize) -> Result<Vec<i128>, String> {
+        (address..address.saturating_add(count))
+            .map(|a| {
+                self.machine
+                    .read_memory(a)
+                    .ok_or(format!("address {} is beyond memory limit", a))
+            })
+            .collect()
+    }
+
+    fn current_instruction(&self) -> String {
+        let ptr = self.machine.get_instr_ptr();
+        let words: Vec<i128> = (ptr..ptr + 4)
+            .map_while(|a| self.machine.read_memory(a))
+            .collect();
+        match disassembler::disassemble_at(&words, ptr).first() {
+            Some(line) => self.mark_current(line.to_string(), line),
+            None => format!("{:>5}: <beyond memory limit>", ptr),
+        }
+    }
+
+    fn mark_current(&self, text: String, line: &disassembler::Line) -> String {
+        let address = match line {
+            disassembler::Line::Code { address, .. } | disassembler::Line::Data { address, .. } => {
+                *address
+            }
+        };
+        if address == self.machine.get_instr_ptr() {
+            format!("=>{}", text)
+        } else {
+            format!("  {}", text)
+        }
+    }
+}
+
+fn describe_state(state: &ExecutionState) -> String {
+    match state {
+        ExecutionState::Working => String::from("working"),
+        ExecutionState::Suspended => String::from("waiting for input"),
+        ExecutionState::Finished => String::from("finished"),
+        ExecutionState::Breakpoint(BreakCause::Address(address)) => {
+            format!("breakpoint at {}", address)
+        }
+        ExecutionState::Breakpoint(BreakCause::Watch { address, value }) => {
+            format!("watchpoint [{}] <- {}", address, value)
+        }
+    }
+}
+
+fn parse_address(arg: Option<&&str>) -> Result<usize, String> {
+    let arg = arg.ok_or("missing address")?;
+    arg.parse::<usize>()
+        .map_err(|_| format!("invalid address '{}'", arg))
+}
+
+fn parse_count(arg: Option<&&str>, default: usize) -> Result<usize, String> {
+    match arg {
+        Some(arg) => arg
+            .parse::<usize>()
+            .map_err(|_| format!("invalid count '{}'", arg)),
+        None => Ok(default),
+    }
+}
+
+/// Parses count of words or entries to show, at most `MAX_SHOWN`.
+fn parse_shown_count(arg: Option<&&str>, default: usize) -> Result<usize, String> {
+    let count = parse_count(arg, default)?;
+    if count > MAX_SHOWN {
+        return Err(format!("count {} exceeds limit of {}", count, MAX_SHOWN));
+    }
+    Ok(count)
+}
+
+fn parse_value(arg: &str) -> Result<i128, String> {
+    arg.parse::<i128>()
+        .map_err(|_| format!("invalid value '{}'", arg))
+}
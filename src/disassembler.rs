@@ -0,0 +1,107 @@
+use std::fmt;
+
+use super::instruction::{Instruction, Mode};
+
+/// Single entry of a disassembly listing.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Line {
+    Code {
+        address: usize,
+        instruction: Instruction,
+        args: Vec<i128>,
+    },
+    Data {
+        address: usize,
+        value: i128,
+    },
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Code {
+                address,
+                instruction,
+                args,
+            } => {
+                write!(f, "{:>5}: {}", address, instruction.opcode.mnemonic())?;
+                for (index, (arg, mode)) in args.iter().zip(&instruction.modes).enumerate() {
+                    let separator = if index == 0 { " " } else { ", " };
+                    write!(f, "{}{}", separator, format_arg(*mode, *arg))?;
+                }
+                Ok(())
+            }
+            Line::Data { address, value } => write!(f, "{:>5}: data {}", address, value),
+        }
+    }
+}
+
+/// Formats argument `value` labelled with its addressing `mode`:
+/// `[12]` for position, `#5` for immediate and `[rb+3]` for relative mode.
+pub fn format_arg(mode: Mode, value: i128) -> String {
+    match mode {
+        Mode::Position => format!("[{}]", value),
+        Mode::Immediate => format!("#{}", value),
+        Mode::Relative if value < 0 => format!("[rb{}]", value),
+        Mode::Relative => format!("[rb+{}]", value),
+    }
+}
+
+/// Disassembles `program` with a linear sweep. Words that don't form
+/// a well formed instruction are reported as data.
+pub fn disassemble(program: &[i128]) -> Vec<Line> {
+    disassemble_at(program, 0)
+}
+
+/// Disassembles memory fragment `words` that starts at address `base`.
+pub fn disassemble_at(words: &[i128], base: usize) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    while offset < words.len() {
+        match decode_at(words, offset) {
+            Some(instruction) => {
+                let args = words[offset + 1..offset + instruction.size()].to_vec();
+                lines.push(Line::Code {
+                    address: base + offset,
+                    instruction,
+                    args,
+                });
+                offset += instruction.size();
+            }
+            None => {
+                lines.push(Line::Data {
+                    address: base + offset,
+                    value: words[offset],
+                });
+                offset += 1;
+            }
+        }
+    }
+
+    lines
+}
+
+/// Disassembles `program` into a printable listing, one line per entry.
+pub fn listing(program: &[i128]) -> String {
+    disassemble(program)
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn decode_at(program: &[i128], address: usize) -> Option<Instruction> {
+    let value = program[address];
+    let instruction = Instruction::decode(address, value).ok()?;
+
+    // unused mode digits wouldn't survive re-assembly
+    if instruction.encode() != value || address + instruction.size() > program.len() {
+        return None;
+    }
+    if let Some(arg) = instruction.opcode.write_arg() {
+        if instruction.modes[arg] == Mode::Immediate {
+            return None;
+        }
+    }
+
+    Some(instruction)
+}
@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use super::input::ParseError;
+use super::interpreter::IntcodeError;
+use super::network::NetworkError;
+
+/// Failures reported by puzzle solvers.
+#[derive(Debug)]
+pub enum AocError {
+    Io(io::Error),
+    /// Puzzle input is well formed but doesn't describe a valid puzzle.
+    InvalidInput(String),
+    /// Puzzle input is malformed.
+    Parse(ParseError),
+    Intcode(IntcodeError),
+    Network(NetworkError),
+    /// Input is well formed but has no answer.
+    NoAnswer(String),
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Io(e) => write!(f, "I/O error: {}", e),
+            AocError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            AocError::Parse(e) => write!(f, "invalid input at {}", e),
+            AocError::Intcode(e) => write!(f, "Intcode error: {}", e),
+            AocError::Network(e) => write!(f, "Intcode network error: {}", e),
+            AocError::NoAnswer(message) => write!(f, "no answer: {}", message),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io(e) => Some(e),
+            AocError::Parse(e) => Some(e),
+            AocError::Intcode(e) => Some(e),
+            AocError::Network(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AocError {
+    fn from(error: io::Error) -> Self {
+        AocError::Io(error)
+    }
+}
+
+impl From<ParseError> for AocError {
+    fn from(error: ParseError) -> Self {
+        AocError::Parse(error)
+    }
+}
+
+impl From<IntcodeError> for AocError {
+    fn from(error: IntcodeError) -> Self {
+        AocError::Intcode(error)
+    }
+}
+
+impl From<NetworkError> for AocError {
+    fn from(error: NetworkError) -> Self {
+        AocError::Network(error)
+    }
+}
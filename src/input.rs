@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Malformed puzzle input. Line and column are counted from 1 and point
+/// at the offending token.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Error located at `token`, which has to be a slice of `text`.
+    fn at(text: &str, token: &str, message: String) -> Self {
+        let offset = (token.as_ptr() as usize)
+            .saturating_sub(text.as_ptr() as usize)
+            .min(text.len());
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        }
+    }
+
+    fn empty() -> Self {
+        ParseError {
+            line: 1,
+            column: 1,
+            message: String::from("input is empty"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Integers separated by commas, e.g. an Intcode program. The list may
+/// span several lines, with or without a comma at the end of a line.
+/// Whitespace around the integers and blank lines are ignored.
+pub fn comma_separated<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let mut values = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        let line = line.strip_suffix(',').unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        for token in line.split(',') {
+            values.push(parse_token(text, token.trim(), "integer")?);
+        }
+    }
+    if values.is_empty() {
+        return Err(ParseError::empty());
+    }
+    Ok(values)
+}
+
+/// One integer per line.
+pub fn integer_lines<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    non_empty_lines(text)?
+        .map(|line| parse_token(text, line.trim(), "integer"))
+        .collect()
+}
+
+/// Lines of tokens separated by `separator`, each token converted by
+/// `parse`. Messages returned by `parse` are reported at the token.
+pub fn token_lines<T, F>(text: &str, separator: char, parse: F) -> Result<Vec<Vec<T>>, ParseError>
+where
+    F: Fn(&str) -> Result<T, String>,
+{
+    non_empty_lines(text)?
+        .map(|line| {
+            line.split(separator)
+                .map(str::trim)
+                .map(|token| parse(token).map_err(|message| ParseError::at(text, token, message)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Pairs written as `A<separator>B`, one per line, like the `A)B` orbits.
+pub fn pairs(text: &str, separator: char) -> Result<Vec<(&str, &str)>, ParseError> {
+    non_empty_lines(text)?
+        .map(|line| {
+            let line = line.trim();
+            match line.split_once(separator) {
+                Some((a, b)) if !a.is_empty() && !b.is_empty() => Ok((a, b)),
+                _ => Err(ParseError::at(
+                    text,
+                    line,
+                    format!("expected A{}B, found '{}'", separator, line),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Digits split into rows of `width`. Rows may be written on separate
+/// lines or run together on one line.
+pub fn digit_grid(text: &str, width: usize) -> Result<Vec<Vec<u8>>, ParseError> {
+    let mut digits = Vec::new();
+    for line in non_empty_lines(text)? {
+        let line = line.trim();
+        for (index, c) in line.char_indices() {
+            match c.to_digit(10) {
+                Some(digit) => digits.push(digit as u8),
+                None => {
+                    return Err(ParseError::at(
+                        text,
+                        &line[index..],
+                        format!("invalid digit '{}'", c),
+                    ))
+                }
+            }
+        }
+    }
+    if width == 0 || !digits.len().is_multiple_of(width) {
+        let end = &text[text.trim_end().len()..];
+        return Err(ParseError::at(
+            text,
+            end,
+            format!("{} digits don't fill rows of {}", digits.len(), width),
+        ));
+    }
+    Ok(digits.chunks(width).map(<[u8]>::to_vec).collect())
+}
+
+/// Lines of `text` without trailing empty lines, failing when there are
+/// none.
+fn non_empty_lines(text: &str) -> Result<std::str::Lines<'_>, ParseError> {
+    let content = text.trim_end();
+    if content.trim_start().is_empty() {
+        return Err(ParseError::empty());
+    }
+    Ok(content.lines())
+}
+
+fn parse_token<T: FromStr>(text: &str, token: &str, what: &str) -> Result<T, ParseError> {
+    token.parse().map_err(|_| {
+        let message = if token.is_empty() {
+            format!("missing {}", what)
+        } else {
+            format!("invalid {} '{}'", what, token)
+        };
+        ParseError::at(text, token, message)
+    })
+}
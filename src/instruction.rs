@@ -0,0 +1,173 @@
+use super::interpreter::IntcodeError;
+
+/// Operation encoded in the two lowest decimal digits of an instruction.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+impl Opcode {
+    pub fn from_code(code: i128) -> Option<Opcode> {
+        match code {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Multiply),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::AdjustRelativeBase),
+            99 => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i128 {
+        match self {
+            Opcode::Add => 1,
+            Opcode::Multiply => 2,
+            Opcode::Input => 3,
+            Opcode::Output => 4,
+            Opcode::JumpIfTrue => 5,
+            Opcode::JumpIfFalse => 6,
+            Opcode::LessThan => 7,
+            Opcode::Equals => 8,
+            Opcode::AdjustRelativeBase => 9,
+            Opcode::Halt => 99,
+        }
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Opcode::Add => "ADD",
+            Opcode::Multiply => "MUL",
+            Opcode::Input => "IN",
+            Opcode::Output => "OUT",
+            Opcode::JumpIfTrue => "JT",
+            Opcode::JumpIfFalse => "JF",
+            Opcode::LessThan => "LT",
+            Opcode::Equals => "EQ",
+            Opcode::AdjustRelativeBase => "ARB",
+            Opcode::Halt => "HALT",
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Opcode> {
+        match mnemonic.to_ascii_uppercase().as_str() {
+            "ADD" => Some(Opcode::Add),
+            "MUL" => Some(Opcode::Multiply),
+            "IN" => Some(Opcode::Input),
+            "OUT" => Some(Opcode::Output),
+            "JT" => Some(Opcode::JumpIfTrue),
+            "JF" => Some(Opcode::JumpIfFalse),
+            "LT" => Some(Opcode::LessThan),
+            "EQ" => Some(Opcode::Equals),
+            "ARB" => Some(Opcode::AdjustRelativeBase),
+            "HALT" => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    /// Index of the argument the instruction writes to, if any.
+    pub fn write_arg(self) -> Option<usize> {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => Some(2),
+            Opcode::Input => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Number of arguments following the instruction word.
+    pub fn arg_count(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+}
+
+/// Addressing mode of a single instruction argument.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    pub fn from_code(code: i128) -> Option<Mode> {
+        match code {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i128 {
+        match self {
+            Mode::Position => 0,
+            Mode::Immediate => 1,
+            Mode::Relative => 2,
+        }
+    }
+}
+
+/// Decoded instruction word. Modes of arguments the opcode doesn't use
+/// are always `Mode::Position`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub modes: [Mode; 3],
+}
+
+impl Instruction {
+    /// Decodes instruction word `value` found at `instr_ptr`.
+    pub fn decode(instr_ptr: usize, value: i128) -> Result<Instruction, IntcodeError> {
+        let opcode = Opcode::from_code(value % 100).ok_or(IntcodeError::UnknownOpcode {
+            instr_ptr,
+            opcode: value,
+        })?;
+
+        let mut modes = [Mode::Position; 3];
+        let mut mode_digits = value / 100;
+        for mode in modes.iter_mut().take(opcode.arg_count()) {
+            let code = mode_digits % 10;
+            *mode = Mode::from_code(code).ok_or(IntcodeError::InvalidMode {
+                instr_ptr,
+                opcode: value,
+                mode: code,
+            })?;
+            mode_digits /= 10;
+        }
+
+        Ok(Instruction { opcode, modes })
+    }
+
+    /// Encodes instruction back into a single memory word.
+    pub fn encode(&self) -> i128 {
+        let modes = self
+            .modes
+            .iter()
+            .rev()
+            .fold(0, |acc, mode| acc * 10 + mode.code());
+        modes * 100 + self.opcode.code()
+    }
+
+    /// Number of memory words taken by the instruction and its arguments.
+    pub fn size(&self) -> usize {
+        self.opcode.arg_count() + 1
+    }
+}
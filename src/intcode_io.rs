@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{Receiver, Sender};
+
+/// Input and output device of an `IntcodeInterpreter`, called on opcodes 3 and 4.
+pub trait IntcodeIo {
+    /// Returns next input value. `None` suspends the program until input
+    /// becomes available.
+    fn read(&mut self) -> Option<i128>;
+
+    fn write(&mut self, value: i128);
+}
+
+/// Default device buffering input and output values in queues.
+#[derive(Clone, Default, Debug)]
+pub struct QueueIo {
+    pub input: VecDeque<i128>,
+    pub output: VecDeque<i128>,
+}
+
+impl IntcodeIo for QueueIo {
+    fn read(&mut self) -> Option<i128> {
+        self.input.pop_front()
+    }
+
+    fn write(&mut self, value: i128) {
+        self.output.push_back(value);
+    }
+}
+
+/// Device forwarding reads and writes to closures.
+pub struct CallbackIo<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R, W> CallbackIo<R, W>
+where
+    R: FnMut() -> Option<i128>,
+    W: FnMut(i128),
+{
+    pub fn new(reader: R, writer: W) -> Self {
+        CallbackIo { reader, writer }
+    }
+}
+
+impl<R, W> IntcodeIo for CallbackIo<R, W>
+where
+    R: FnMut() -> Option<i128>,
+    W: FnMut(i128),
+{
+    fn read(&mut self) -> Option<i128> {
+        (self.reader)()
+    }
+
+    fn write(&mut self, value: i128) {
+        (self.writer)(value)
+    }
+}
+
+/// Device backed by std channels. Reads block until a value arrives and
+/// suspend the program once all senders are gone. Values written after
+/// the receiving side hung up are dropped.
+pub struct ChannelIo {
+    input: Receiver<i128>,
+    output: Sender<i128>,
+}
+
+impl ChannelIo {
+    pub fn new(input: Receiver<i128>, output: Sender<i128>) -> Self {
+        ChannelIo { input, output }
+    }
+}
+
+impl IntcodeIo for ChannelIo {
+    fn read(&mut self) -> Option<i128> {
+        self.input.recv().ok()
+    }
+
+    fn write(&mut self, value: i128) {
+        let _ = self.output.send(value);
+    }
+}
+
+/// Chunk of output read by `IntcodeInterpreter::get_ascii_output`.
+#[derive(PartialEq, Clone, Debug)]
+pub enum AsciiOutput {
+    /// Text up to a newline or the end of pending output, without the newline.
+    Line(String),
+    /// Value outside of the ASCII range.
+    Value(i128),
+}
+
+pub fn is_ascii_value(value: i128) -> bool {
+    (0..=127).contains(&value)
+}
+
+/// Device translating between ASCII Intcode programs and text streams,
+/// e.g. stdin and stdout. Input is read a line at a time when the program
+/// asks for it; values outside of the ASCII range are written as numbers
+/// on a separate line.
+pub struct AsciiIo<R, W> {
+    reader: R,
+    writer: W,
+    pending: VecDeque<i128>,
+}
+
+impl<R: BufRead, W: Write> AsciiIo<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        AsciiIo {
+            reader,
+            writer,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> IntcodeIo for AsciiIo<R, W> {
+    fn read(&mut self) -> Option<i128> {
+        if self.pending.is_empty() {
+            let _ = self.writer.flush();
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return None,
+                Ok(_) => {}
+            }
+            let line = line.trim_end_matches(['\n', '\r']);
+            self.pending.extend(line.chars().map(|c| c as i128));
+            self.pending.push_back(i128::from(b'\n'));
+        }
+        self.pending.pop_front()
+    }
+
+    fn write(&mut self, value: i128) {
+        let _ = if is_ascii_value(value) {
+            write!(self.writer, "{}", value as u8 as char)
+        } else {
+            writeln!(self.writer, "{}", value)
+        };
+    }
+}
@@ -1,196 +1,821 @@
-use std::collections::VecDeque;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::str::FromStr;
+
+use super::input::{self, ParseError};
+use super::instruction::{Instruction, Mode, Opcode};
+use super::intcode_io::{self, AsciiOutput, IntcodeIo, QueueIo};
+use super::memory::Memory;
+use super::trace::{TraceEntry, TraceSink};
+
+/// Default upper bound for interpreter memory, in words.
+pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// Version of the format written by `IntcodeInterpreter::save_snapshot`.
+pub const SNAPSHOT_VERSION: u32 = 1;
+const SNAPSHOT_MAGIC: &str = "intcode-snapshot";
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum ExecutionState {
     Working,
     Suspended,
     Finished,
+    Breakpoint(BreakCause),
+}
+
+/// Reason `execute` stopped on `ExecutionState::Breakpoint`.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BreakCause {
+    /// Instruction pointer reached a breakpoint, the instruction wasn't executed yet.
+    Address(usize),
+    /// Last executed instruction wrote `value` to a watched `address`.
+    Watch { address: usize, value: i128 },
+}
+
+/// Outcome of a single `IntcodeInterpreter::step`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Step {
+    pub instr_ptr: usize,
+    pub instruction: Instruction,
+    /// Memory write done by the instruction as `(address, value)`.
+    pub write: Option<(usize, i128)>,
+    pub state: ExecutionState,
+}
+
+/// Failures reported by `IntcodeInterpreter`. Execution errors carry the
+/// instruction pointer and opcode of the instruction that faulted.
+#[derive(Debug)]
+pub enum IntcodeError {
+    Io(io::Error),
+    EmptyProgram,
+    InvalidProgram(ParseError),
+    UnknownOpcode {
+        instr_ptr: usize,
+        opcode: i128,
+    },
+    InvalidMode {
+        instr_ptr: usize,
+        opcode: i128,
+        mode: i128,
+    },
+    InvalidAddress {
+        instr_ptr: usize,
+        opcode: i128,
+        address: i128,
+    },
+    MemoryLimitExceeded {
+        instr_ptr: usize,
+        opcode: i128,
+        address: usize,
+        limit: usize,
+    },
+    ArithmeticOverflow {
+        instr_ptr: usize,
+        opcode: i128,
+    },
+    InvalidSnapshot {
+        line: Option<usize>,
+        message: String,
+    },
+    IncompatibleSnapshot {
+        version: String,
+    },
 }
 
+impl fmt::Display for IntcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntcodeError::Io(e) => write!(f, "I/O error: {}", e),
+            IntcodeError::EmptyProgram => write!(f, "program is empty"),
+            IntcodeError::InvalidProgram(e) => write!(f, "invalid program: {}", e),
+            IntcodeError::UnknownOpcode { instr_ptr, opcode } => {
+                write!(f, "[pc:{}] unknown opcode {}", instr_ptr, opcode)
+            }
+            IntcodeError::InvalidMode {
+                instr_ptr,
+                opcode,
+                mode,
+            } => write!(
+                f,
+                "[pc:{}] opcode {} uses invalid argument mode {}",
+                instr_ptr, opcode, mode
+            ),
+            IntcodeError::InvalidAddress {
+                instr_ptr,
+                opcode,
+                address,
+            } => write!(
+                f,
+                "[pc:{}] opcode {} accessed invalid address {}",
+                instr_ptr, opcode, address
+            ),
+            IntcodeError::MemoryLimitExceeded {
+                instr_ptr,
+                opcode,
+                address,
+                limit,
+            } => write!(
+                f,
+                "[pc:{}] opcode {} accessed address {} beyond memory limit {}",
+                instr_ptr, opcode, address, limit
+            ),
+            IntcodeError::ArithmeticOverflow { instr_ptr, opcode } => {
+                write!(f, "[pc:{}] opcode {} overflowed", instr_ptr, opcode)
+            }
+            IntcodeError::InvalidSnapshot {
+                line: Some(line),
+                message,
+            } => write!(f, "invalid snapshot at line {}: {}", line, message),
+            IntcodeError::InvalidSnapshot {
+                line: None,
+                message,
+            } => write!(f, "invalid snapshot: {}", message),
+            IntcodeError::IncompatibleSnapshot { version } => write!(
+                f,
+                "snapshot version {} is not supported, expected {}",
+                version, SNAPSHOT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for IntcodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IntcodeError::Io(e) => Some(e),
+            IntcodeError::InvalidProgram(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IntcodeError {
+    fn from(e: io::Error) -> Self {
+        IntcodeError::Io(e)
+    }
+}
+
+/// Reads comma separated Intcode program from `input_file`.
+pub fn read_program(input_file: &str) -> Result<Vec<i128>, IntcodeError> {
+    parse_program(&fs::read_to_string(input_file)?)
+}
+
+/// Parses comma separated Intcode program, possibly spread over several
+/// lines.
+pub fn parse_program(text: &str) -> Result<Vec<i128>, IntcodeError> {
+    input::comma_separated(text).map_err(IntcodeError::InvalidProgram)
+}
+
+/// Intcode machine talking to the outside world through an `IntcodeIo`
+/// device, by default a pair of queues.
 #[derive(Clone)]
-pub struct IntcodeInterpreter {
-    memory: Vec<i128>,
+pub struct IntcodeInterpreter<I: IntcodeIo = QueueIo> {
+    memory: Memory,
+    memory_limit: usize,
     instr_ptr: usize,
+    relative_base: i128,
+    instr_count: u64,
+    last_write: Option<(usize, i128)>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<usize>,
     state: ExecutionState,
-    input: VecDeque<i32>,
-    output: VecDeque<i32>,
+    io: I,
 }
 
 impl IntcodeInterpreter {
-    pub fn new(instuctions: &Vec<i128>) -> IntcodeInterpreter {
-        IntcodeInterpreter {
-            memory: instuctions.clone(),
+    pub fn new(instuctions: &[i128]) -> Result<IntcodeInterpreter, IntcodeError> {
+        if instuctions.is_empty() {
+            return Err(IntcodeError::EmptyProgram);
+        }
+
+        Ok(IntcodeInterpreter {
+            memory: Memory::new(instuctions),
+            memory_limit: DEFAULT_MEMORY_LIMIT,
             instr_ptr: 0,
-            input: VecDeque::new(),
-            output: VecDeque::new(),
+            relative_base: 0,
+            instr_count: 0,
+            last_write: None,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
             state: ExecutionState::Working,
+            io: QueueIo::default(),
+        })
+    }
+
+    pub fn from_file(input_file: &str) -> Result<IntcodeInterpreter, IntcodeError> {
+        IntcodeInterpreter::new(&read_program(input_file)?)
+    }
+
+    pub fn set_input(&mut self, input: i128) {
+        self.io.input.push_back(input);
+    }
+
+    pub fn get_output(&mut self) -> Option<i128> {
+        self.io.output.pop_front()
+    }
+
+    /// Queues `line` as character codes followed by a newline.
+    pub fn set_input_line(&mut self, line: &str) {
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        for c in line.chars() {
+            self.set_input(c as i128);
         }
+        self.set_input(i128::from(b'\n'));
     }
 
-    pub fn from_file(input_file: &str) -> IntcodeInterpreter {
-        let mut buffered = BufReader::new(File::open(input_file).unwrap());
-        let mut input_data = String::new();
-        let r = buffered.read_line(&mut input_data);
-        if r.is_err() {
-            panic!("Failed to read input data!")
+    /// Reads pending output as text up to the next newline. A value
+    /// outside of the ASCII range is returned on its own as a raw number.
+    pub fn get_ascii_output(&mut self) -> Option<AsciiOutput> {
+        let first = *self.io.output.front()?;
+        if !intcode_io::is_ascii_value(first) {
+            self.io.output.pop_front();
+            return Some(AsciiOutput::Value(first));
         }
-        let instructions = input_data
-            .split(',')
-            .map(|c| c.parse::<i128>().unwrap())
-            .collect();
 
-        IntcodeInterpreter {
-            memory: instructions,
-            instr_ptr: 0,
-            input: VecDeque::new(),
-            output: VecDeque::new(),
-            state: ExecutionState::Working,
+        let mut line = String::new();
+        while let Some(&value) = self.io.output.front() {
+            if !intcode_io::is_ascii_value(value) {
+                break;
+            }
+            self.io.output.pop_front();
+            if value == i128::from(b'\n') {
+                break;
+            }
+            line.push(value as u8 as char);
         }
+        Some(AsciiOutput::Line(line))
     }
 
-    pub fn read_memory(&self, address: usize) -> Option<i128> {
-        let r = self.memory.get(address);
+    /// Writes complete machine state: memory, registers, execution state
+    /// and pending input and output. Breakpoints and watchpoints are
+    /// debugging aids and are not saved.
+    ///
+    /// The format is text, one `key value` pair per line after a
+    /// `intcode-snapshot <version>` header. Lists are comma separated:
+    ///
+    /// ```text
+    /// intcode-snapshot 1
+    /// memory_limit 16777216
+    /// instr_ptr 2
+    /// relative_base 0
+    /// instr_count 1
+    /// state suspended
+    /// memory 3,9,4,9,99
+    /// input
+    /// output 42
+    /// ```
+    ///
+    /// `state` is one of `working`, `suspended`, `finished`,
+    /// `breakpoint <address>` or `watch <address> <value>`.
+    pub fn save_snapshot<W: Write>(&self, out: &mut W) -> Result<(), IntcodeError> {
+        let state = match self.state {
+            ExecutionState::Working => String::from("working"),
+            ExecutionState::Suspended => String::from("suspended"),
+            ExecutionState::Finished => String::from("finished"),
+            ExecutionState::Breakpoint(BreakCause::Address(address)) => {
+                format!("breakpoint {}", address)
+            }
+            ExecutionState::Breakpoint(BreakCause::Watch { address, value }) => {
+                format!("watch {} {}", address, value)
+            }
+        };
 
-        if r.is_some() {
-            return Some(*r.unwrap());
+        writeln!(out, "{} {}", SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        writeln!(out, "memory_limit {}", self.memory_limit)?;
+        writeln!(out, "instr_ptr {}", self.instr_ptr)?;
+        writeln!(out, "relative_base {}", self.relative_base)?;
+        writeln!(out, "instr_count {}", self.instr_count)?;
+        writeln!(out, "state {}", state)?;
+        writeln!(out, "memory{}", join_values(self.memory.to_vec().iter()))?;
+        writeln!(out, "input{}", join_values(self.io.input.iter()))?;
+        writeln!(out, "output{}", join_values(self.io.output.iter()))?;
+        Ok(())
+    }
+
+    pub fn save_snapshot_file(&self, snapshot_file: &str) -> Result<(), IntcodeError> {
+        let mut out = BufWriter::new(File::create(snapshot_file)?);
+        self.save_snapshot(&mut out)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Restores machine written by `save_snapshot`. Snapshots written by
+    /// a different format version are rejected.
+    pub fn load_snapshot<R: BufRead>(input: R) -> Result<IntcodeInterpreter, IntcodeError> {
+        let mut lines = input.lines();
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let version = match header.split_whitespace().collect::<Vec<&str>>()[..] {
+            [SNAPSHOT_MAGIC, version] => version.to_string(),
+            _ => {
+                return Err(IntcodeError::InvalidSnapshot {
+                    line: Some(1),
+                    message: String::from("missing snapshot header"),
+                })
+            }
+        };
+        if version != SNAPSHOT_VERSION.to_string() {
+            return Err(IntcodeError::IncompatibleSnapshot { version });
+        }
+
+        let mut fields: HashMap<String, (usize, String)> = HashMap::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut parts = line.trim().splitn(2, ' ');
+            let key = parts.next().unwrap_or_default().to_string();
+            let value = parts.next().unwrap_or_default().trim().to_string();
+            fields.insert(key, (index + 2, value));
         }
-        None
+
+        let field = |key: &str| snapshot_field(&fields, key);
+        let invalid = |line: usize, key: &str| IntcodeError::InvalidSnapshot {
+            line: Some(line),
+            message: format!("invalid value of '{}'", key),
+        };
+        let values = |key: &str| -> Result<Vec<i128>, IntcodeError> {
+            let (line, value) = field(key)?;
+            value
+                .split(',')
+                .filter(|v| !v.is_empty())
+                .map(|v| v.parse::<i128>().map_err(|_| invalid(*line, key)))
+                .collect()
+        };
+
+        let (state_line, state) = field("state")?;
+        let state_words: Vec<&str> = state.split_whitespace().collect();
+        let state = match state_words[..] {
+            ["working"] => ExecutionState::Working,
+            ["suspended"] => ExecutionState::Suspended,
+            ["finished"] => ExecutionState::Finished,
+            ["breakpoint", address] => ExecutionState::Breakpoint(BreakCause::Address(
+                address.parse().map_err(|_| invalid(*state_line, "state"))?,
+            )),
+            ["watch", address, value] => ExecutionState::Breakpoint(BreakCause::Watch {
+                address: address.parse().map_err(|_| invalid(*state_line, "state"))?,
+                value: value.parse().map_err(|_| invalid(*state_line, "state"))?,
+            }),
+            _ => return Err(invalid(*state_line, "state")),
+        };
+
+        let mut machine = IntcodeInterpreter::new(&values("memory")?)?;
+        machine.memory_limit = parse_snapshot_field(&fields, "memory_limit")?;
+        machine.instr_ptr = parse_snapshot_field(&fields, "instr_ptr")?;
+        machine.relative_base = parse_snapshot_field(&fields, "relative_base")?;
+        machine.instr_count = parse_snapshot_field(&fields, "instr_count")?;
+        machine.state = state;
+        machine.io.input = values("input")?.into_iter().collect::<VecDeque<i128>>();
+        machine.io.output = values("output")?.into_iter().collect::<VecDeque<i128>>();
+        Ok(machine)
+    }
+
+    pub fn load_snapshot_file(snapshot_file: &str) -> Result<IntcodeInterpreter, IntcodeError> {
+        IntcodeInterpreter::load_snapshot(BufReader::new(File::open(snapshot_file)?))
+    }
+}
+
+fn snapshot_field<'a>(
+    fields: &'a HashMap<String, (usize, String)>,
+    key: &str,
+) -> Result<&'a (usize, String), IntcodeError> {
+    fields
+        .get(key)
+        .ok_or_else(|| IntcodeError::InvalidSnapshot {
+            line: None,
+            message: format!("missing field '{}'", key),
+        })
+}
+
+fn parse_snapshot_field<T: FromStr>(
+    fields: &HashMap<String, (usize, String)>,
+    key: &str,
+) -> Result<T, IntcodeError> {
+    let (line, value) = snapshot_field(fields, key)?;
+    value
+        .parse::<T>()
+        .map_err(|_| IntcodeError::InvalidSnapshot {
+            line: Some(*line),
+            message: format!("invalid value of '{}'", key),
+        })
+}
+
+/// Formats list field value, including the separating space when not empty.
+fn join_values<'a, T: Iterator<Item = &'a i128>>(values: T) -> String {
+    let values: Vec<String> = values.map(i128::to_string).collect();
+    if values.is_empty() {
+        String::new()
+    } else {
+        format!(" {}", values.join(","))
+    }
+}
+
+impl<I: IntcodeIo> IntcodeInterpreter<I> {
+    /// Replaces I/O device, returning the machine with the new device
+    /// together with the old one.
+    pub fn replace_io<J: IntcodeIo>(self, io: J) -> (IntcodeInterpreter<J>, I) {
+        let machine = IntcodeInterpreter {
+            memory: self.memory,
+            memory_limit: self.memory_limit,
+            instr_ptr: self.instr_ptr,
+            relative_base: self.relative_base,
+            instr_count: self.instr_count,
+            last_write: self.last_write,
+            breakpoints: self.breakpoints,
+            watchpoints: self.watchpoints,
+            state: self.state,
+            io,
+        };
+        (machine, self.io)
+    }
+
+    pub fn with_io<J: IntcodeIo>(self, io: J) -> IntcodeInterpreter<J> {
+        self.replace_io(io).0
+    }
+
+    pub fn io(&self) -> &I {
+        &self.io
+    }
+
+    pub fn io_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+
+    /// Sets the maximum number of memory words a program may use.
+    /// Memory grows on demand up to this limit.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = limit;
+    }
+
+    /// Returns value stored at `address`. Addresses past the end of the
+    /// program read as zero, addresses beyond the memory limit yield `None`.
+    pub fn read_memory(&self, address: usize) -> Option<i128> {
+        if address >= self.memory_limit {
+            return None;
+        }
+        Some(self.load(address))
+    }
+
+    /// Number of instructions executed since the program was loaded.
+    pub fn get_instruction_count(&self) -> u64 {
+        self.instr_count
     }
 
     pub fn get_state(&self) -> &ExecutionState {
         &self.state
     }
 
-    pub fn set_input(&mut self, input: i32) {
-        self.input.push_back(input);
-        // println!("{:?}", self.input);
+    pub fn set_memory(&mut self, address: usize, value: i128) -> Result<(), IntcodeError> {
+        self.store(address, value)
+    }
+
+    pub fn get_instr_ptr(&self) -> usize {
+        self.instr_ptr
+    }
+
+    pub fn get_relative_base(&self) -> i128 {
+        self.relative_base
+    }
+
+    /// Makes `execute` stop before running the instruction at `address`.
+    pub fn add_breakpoint(&mut self, address: usize) {
+        self.breakpoints.insert(address);
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn get_breakpoints(&self) -> impl Iterator<Item = &usize> {
+        self.breakpoints.iter()
+    }
+
+    /// Makes `execute` stop after an instruction writes to `address`.
+    pub fn add_watchpoint(&mut self, address: usize) {
+        self.watchpoints.insert(address);
     }
 
-    pub fn get_output(&mut self) -> Option<i32> {
-        self.output.pop_front()
+    pub fn remove_watchpoint(&mut self, address: usize) -> bool {
+        self.watchpoints.remove(&address)
     }
 
-    pub fn set_memory(&mut self, address: usize, value: i128) {
-        self.memory[address] = value;
+    pub fn get_watchpoints(&self) -> impl Iterator<Item = &usize> {
+        self.watchpoints.iter()
     }
 
-    pub fn execute(&mut self) -> ExecutionState {
+    /// Runs until the program finishes, waits for input or hits a breakpoint.
+    /// When resuming after a breakpoint or waiting for input, a breakpoint
+    /// on the current instruction is passed over.
+    pub fn execute(&mut self) -> Result<ExecutionState, IntcodeError> {
+        self.run(None)
+    }
+
+    /// Like `execute`, passing a `TraceEntry` for every executed
+    /// instruction to `sink` as it goes.
+    pub fn execute_traced(
+        &mut self,
+        sink: &mut dyn TraceSink,
+    ) -> Result<ExecutionState, IntcodeError> {
+        self.run(Some(sink))
+    }
+
+    // Inlined into both callers so that `execute` drops the tracing branch.
+    #[inline(always)]
+    fn run(
+        &mut self,
+        mut trace: Option<&mut dyn TraceSink>,
+    ) -> Result<ExecutionState, IntcodeError> {
+        let mut resume_from = match self.state {
+            ExecutionState::Breakpoint(BreakCause::Address(_)) | ExecutionState::Suspended => {
+                Some(self.instr_ptr)
+            }
+            _ => None,
+        };
         self.state = ExecutionState::Working;
         while self.state == ExecutionState::Working {
-            let instr_code = self.memory[self.instr_ptr] % 100;
-            let arg_modes = format!("{:010}", (self.memory[self.instr_ptr] / 100) as i32);
-
-            // print!("[pc:{}][{}] instr:{}, modes:{}",self.instr_ptr,self.memory[self.instr_ptr],instr_code, arg_modes );
-
-            match instr_code {
-                1 => self.execute_addition(arg_modes.as_str()),
-                2 => self.execute_multiplication(arg_modes.as_str()),
-                3 => self.state = self.read_input(),
-                4 => self.write_output(arg_modes.as_str()),
-                5 => self.jmp_if_true(arg_modes.as_str()),
-                6 => self.jmp_if_false(arg_modes.as_str()),
-                7 => self.less_than(arg_modes.as_str()),
-                8 => self.equal(arg_modes.as_str()),
-                99 => self.state = ExecutionState::Finished,
-                _ => panic!("Unsupported instruction!"),
+            if !self.breakpoints.is_empty()
+                && resume_from.take() != Some(self.instr_ptr)
+                && self.breakpoints.contains(&self.instr_ptr)
+            {
+                self.state = ExecutionState::Breakpoint(BreakCause::Address(self.instr_ptr));
+                break;
+            }
+
+            let instr = Instruction::decode(self.instr_ptr, self.load(self.instr_ptr))?;
+            match trace.as_mut() {
+                None => self.execute_instruction(&instr)?,
+                Some(sink) => self.execute_traced_instruction(&instr, &mut **sink)?,
+            }
+
+            if let Some((address, value)) = self.last_write {
+                if self.state == ExecutionState::Working && self.watchpoints.contains(&address) {
+                    self.state = ExecutionState::Breakpoint(BreakCause::Watch { address, value });
+                }
             }
         }
 
-        self.state.clone()
+        Ok(self.state.clone())
     }
 
-    fn read_arg_value(&self, pos: u32, modes: &str) -> i128 {
-        let a = self.memory[self.instr_ptr + pos as usize];
-        // print!("<{} = {}>", modes.chars().rev().nth((pos-1) as usize).unwrap(), pos);
-        if modes.chars().rev().nth((pos - 1) as usize).unwrap() == '0' {
-            // print!(" mem val at {}, ", a);
-            return self.memory[a as usize];
+    /// Executes exactly one instruction, ignoring breakpoints and watchpoints.
+    pub fn step(&mut self) -> Result<Step, IntcodeError> {
+        let instr_ptr = self.instr_ptr;
+        let instruction = Instruction::decode(instr_ptr, self.load(instr_ptr))?;
+        self.state = ExecutionState::Working;
+        self.execute_instruction(&instruction)?;
+
+        Ok(Step {
+            instr_ptr,
+            instruction,
+            write: self.last_write,
+            state: self.state.clone(),
+        })
+    }
+
+    #[inline(never)]
+    fn execute_traced_instruction(
+        &mut self,
+        instr: &Instruction,
+        sink: &mut dyn TraceSink,
+    ) -> Result<(), IntcodeError> {
+        let instr_ptr = self.instr_ptr;
+        let operands = self.trace_operands(instr);
+        self.execute_instruction(instr)?;
+        if self.state != ExecutionState::Suspended {
+            sink.record(&TraceEntry {
+                instr_ptr,
+                instruction: *instr,
+                operands,
+                write: self.last_write,
+            })?;
         }
-        // print!(" immediate val {}, ", a);
-        a
+        Ok(())
     }
 
-    fn execute_addition(&mut self, arg_modes: &str) {
-        // let a1 = self.memory[self.instr_ptr + 1];
-        // let a2 = self.memory[self.instr_ptr + 2];
-        let o = self.memory[self.instr_ptr + 3];
+    fn execute_instruction(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        self.last_write = None;
 
-        // let v1 = if arg_modes%10==0 {self.memory[a1 as usize]} else {a1};
-        // let v2 = if arg_modes%100==0 {self.memory[a2 as usize]} else {a2};
-        let v1 = self.read_arg_value(1, arg_modes);
-        let v2 = self.read_arg_value(2, arg_modes);
+        match instr.opcode {
+            Opcode::Add => self.execute_addition(instr)?,
+            Opcode::Multiply => self.execute_multiplication(instr)?,
+            Opcode::Input => self.state = self.read_input(instr)?,
+            Opcode::Output => self.write_output(instr)?,
+            Opcode::JumpIfTrue => self.jmp_if_true(instr)?,
+            Opcode::JumpIfFalse => self.jmp_if_false(instr)?,
+            Opcode::LessThan => self.less_than(instr)?,
+            Opcode::Equals => self.equal(instr)?,
+            Opcode::AdjustRelativeBase => self.adjust_relative_base(instr)?,
+            Opcode::Halt => self.state = ExecutionState::Finished,
+        }
+        if self.state != ExecutionState::Suspended {
+            self.instr_count += 1;
+        }
+        Ok(())
+    }
 
-        // println!("{} = {} + {}", o, v1, v2 );
-        self.memory[o as usize] = v1 + v2;
+    fn trace_operands(&self, instr: &Instruction) -> [Option<i128>; 3] {
+        let mut operands = [None; 3];
+        for (arg, operand) in operands
+            .iter_mut()
+            .enumerate()
+            .take(instr.opcode.arg_count())
+        {
+            *operand = if instr.opcode.write_arg() == Some(arg) {
+                self.write_arg_address(arg + 1, instr)
+                    .ok()
+                    .map(|a| a as i128)
+            } else {
+                self.read_arg_value(arg + 1, instr).ok()
+            };
+        }
+        operands
+    }
+
+    fn opcode(&self) -> i128 {
+        self.load(self.instr_ptr)
+    }
+
+    #[inline]
+    fn load(&self, address: usize) -> i128 {
+        self.memory.get(address)
+    }
+
+    #[inline(always)]
+    fn checked_load(&self, address: usize) -> Result<i128, IntcodeError> {
+        if address >= self.memory_limit {
+            return Err(self.limit_exceeded(address));
+        }
+        Ok(self.load(address))
+    }
+
+    #[inline(always)]
+    fn store(&mut self, address: usize, value: i128) -> Result<(), IntcodeError> {
+        if address >= self.memory_limit {
+            return Err(self.limit_exceeded(address));
+        }
+        self.memory.set(address, value);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_result(&mut self, address: usize, value: i128) -> Result<(), IntcodeError> {
+        self.store(address, value)?;
+        self.last_write = Some((address, value));
+        Ok(())
+    }
+
+    fn limit_exceeded(&self, address: usize) -> IntcodeError {
+        IntcodeError::MemoryLimitExceeded {
+            instr_ptr: self.instr_ptr,
+            opcode: self.opcode(),
+            address,
+            limit: self.memory_limit,
+        }
+    }
+
+    #[inline]
+    fn to_address(&self, address: i128) -> Result<usize, IntcodeError> {
+        usize::try_from(address).map_err(|_| IntcodeError::InvalidAddress {
+            instr_ptr: self.instr_ptr,
+            opcode: self.opcode(),
+            address,
+        })
+    }
+
+    #[inline]
+    fn relative_address(&self, offset: i128) -> Result<usize, IntcodeError> {
+        let address = self
+            .relative_base
+            .checked_add(offset)
+            .ok_or_else(|| self.overflow())?;
+        self.to_address(address)
+    }
+
+    fn overflow(&self) -> IntcodeError {
+        IntcodeError::ArithmeticOverflow {
+            instr_ptr: self.instr_ptr,
+            opcode: self.opcode(),
+        }
+    }
+
+    #[inline(always)]
+    fn read_arg_value(&self, pos: usize, instr: &Instruction) -> Result<i128, IntcodeError> {
+        let a = self.checked_load(self.instr_ptr + pos)?;
+        match instr.modes[pos - 1] {
+            Mode::Position => self.checked_load(self.to_address(a)?),
+            Mode::Immediate => Ok(a),
+            Mode::Relative => self.checked_load(self.relative_address(a)?),
+        }
+    }
+
+    #[inline(always)]
+    fn write_arg_address(&self, pos: usize, instr: &Instruction) -> Result<usize, IntcodeError> {
+        let a = self.checked_load(self.instr_ptr + pos)?;
+        match instr.modes[pos - 1] {
+            Mode::Position => self.to_address(a),
+            Mode::Relative => self.relative_address(a),
+            Mode::Immediate => Err(IntcodeError::InvalidMode {
+                instr_ptr: self.instr_ptr,
+                opcode: self.opcode(),
+                mode: 1,
+            }),
+        }
+    }
+
+    fn execute_addition(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let o = self.write_arg_address(3, instr)?;
+
+        let v1 = self.read_arg_value(1, instr)?;
+        let v2 = self.read_arg_value(2, instr)?;
+
+        let r = v1.checked_add(v2).ok_or_else(|| self.overflow())?;
+        self.write_result(o, r)?;
         self.instr_ptr += 4;
+        Ok(())
     }
 
-    fn execute_multiplication(&mut self, arg_modes: &str) {
-        let v1 = self.read_arg_value(1, arg_modes);
-        let v2 = self.read_arg_value(2, arg_modes);
-        let o = self.memory[self.instr_ptr + 3];
+    fn execute_multiplication(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let v1 = self.read_arg_value(1, instr)?;
+        let v2 = self.read_arg_value(2, instr)?;
+        let o = self.write_arg_address(3, instr)?;
 
-        // println!("{} = {} * {}", o, v1, v2 );
-        self.memory[o as usize] = v1 * v2;
+        let r = v1.checked_mul(v2).ok_or_else(|| self.overflow())?;
+        self.write_result(o, r)?;
         self.instr_ptr += 4;
+        Ok(())
     }
 
-    fn read_input(&mut self) -> ExecutionState {
-        let r = self.memory[self.instr_ptr + 1];
-        // println!("read input at addr {}", r);
-        let input = self.input.pop_front();
-        if input.is_none() {
-            return ExecutionState::Suspended;
+    fn read_input(&mut self, instr: &Instruction) -> Result<ExecutionState, IntcodeError> {
+        let r = self.write_arg_address(1, instr)?;
+        match self.io.read() {
+            Some(input) => {
+                self.write_result(r, input)?;
+                self.instr_ptr += 2;
+                Ok(ExecutionState::Working)
+            }
+            None => Ok(ExecutionState::Suspended),
         }
-        self.memory[r as usize] = input.unwrap() as i128;
-        self.instr_ptr += 2;
-        ExecutionState::Working
     }
 
-    fn write_output(&mut self, arg_modes: &str) {
-        // let r = self.memory[self.instr_ptr + 1];
-        let r = self.read_arg_value(1, arg_modes);
+    fn write_output(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let r = self.read_arg_value(1, instr)?;
 
-        self.output.push_back(r as i32);
+        self.io.write(r);
         self.instr_ptr += 2;
-        // println!(" output value => {}", r);
+        Ok(())
     }
 
-    fn jmp_if_true(&mut self, arg_modes: &str) {
-        let r = self.read_arg_value(1, arg_modes);
+    fn jmp_if_true(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let r = self.read_arg_value(1, instr)?;
         if r != 0 {
-            self.instr_ptr = self.read_arg_value(2, arg_modes) as usize;
+            self.instr_ptr = self.to_address(self.read_arg_value(2, instr)?)?;
         } else {
             self.instr_ptr += 3;
         }
+        Ok(())
     }
 
-    fn jmp_if_false(&mut self, arg_modes: &str) {
-        let r = self.read_arg_value(1, arg_modes);
+    fn jmp_if_false(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let r = self.read_arg_value(1, instr)?;
         if r == 0 {
-            self.instr_ptr = self.read_arg_value(2, arg_modes) as usize;
+            self.instr_ptr = self.to_address(self.read_arg_value(2, instr)?)?;
         } else {
             self.instr_ptr += 3;
         }
+        Ok(())
     }
 
-    fn less_than(&mut self, arg_modes: &str) {
-        let r1 = self.read_arg_value(1, arg_modes);
-        let r2 = self.read_arg_value(2, arg_modes);
-        let o = self.memory[self.instr_ptr + 3];
+    fn less_than(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let r1 = self.read_arg_value(1, instr)?;
+        let r2 = self.read_arg_value(2, instr)?;
+        let o = self.write_arg_address(3, instr)?;
 
-        self.memory[o as usize] = if r1 < r2 { 1 } else { 0 };
+        self.write_result(o, if r1 < r2 { 1 } else { 0 })?;
         self.instr_ptr += 4;
+        Ok(())
     }
 
-    fn equal(&mut self, arg_modes: &str) {
-        let r1 = self.read_arg_value(1, arg_modes);
-        let r2 = self.read_arg_value(2, arg_modes);
-        let o = self.memory[self.instr_ptr + 3];
+    fn equal(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let r1 = self.read_arg_value(1, instr)?;
+        let r2 = self.read_arg_value(2, instr)?;
+        let o = self.write_arg_address(3, instr)?;
 
-        self.memory[o as usize] = if r1 == r2 { 1 } else { 0 };
+        self.write_result(o, if r1 == r2 { 1 } else { 0 })?;
         self.instr_ptr += 4;
+        Ok(())
+    }
+
+    fn adjust_relative_base(&mut self, instr: &Instruction) -> Result<(), IntcodeError> {
+        let offset = self.read_arg_value(1, instr)?;
+        self.relative_base = self
+            .relative_base
+            .checked_add(offset)
+            .ok_or_else(|| self.overflow())?;
+        self.instr_ptr += 2;
+        Ok(())
     }
 }
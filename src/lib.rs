@@ -0,0 +1,29 @@
+//! Advent of Code 2019 puzzle solutions and the Intcode toolchain they
+//! share. Each `dayNN` module implements `solver::Solver` for its puzzle
+//! and is registered in `solver::SOLVERS`; `verify` checks answers
+//! against known ones.
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+
+pub mod assembler;
+pub mod bench;
+pub mod debugger;
+pub mod disassembler;
+pub mod error;
+pub mod input;
+pub mod instruction;
+pub mod intcode_io;
+pub mod interpreter;
+mod memory;
+pub mod network;
+pub mod permutations;
+pub mod solver;
+pub mod trace;
+pub mod verify;
@@ -1,28 +1,459 @@
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::process;
+use std::time::Duration;
 
-mod interpreter;
+use aoc2019::error::AocError;
+use aoc2019::solver::{self, Parameters, SOLVERS};
+use aoc2019::verify::{ExpectedAnswers, Verdict};
+use aoc2019::{assembler, bench, debugger, disassembler, intcode_io, interpreter, trace};
 
 fn main() {
-    println!("Hello, Advent of Code 2019!");
-    day01::solve("./data/day01.txt");
-    day01::solve_pt2("./data/day01.txt");
-    day02::solve("./data/day02.txt");
-    day02::solve_pt2("./data/day02.txt", 19690720);
-    day03::solve("./data/day03.txt");
-    day04::solve(168630, 718098);
-    day05::solve("./data/day05.txt");
-    day05::solve_pt2("./data/day05.txt");
-    day06::solve("./data/day06.txt");
-    day06::solve_pt2("./data/day06.txt");
-    day07::solve("./data/day07.txt");
-    day07::solve_pt2("./data/day07.txt");
-    day08::solve("./data/day08.txt");
-    day08::solve_pt2("./data/day08.txt", true);
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("ascii") => run_ascii(args.get(2)),
+        Some("asm") => assemble(args.get(2)),
+        Some("debug") => debug(&args[2..]),
+        Some("disasm") => disassemble(args.get(2)),
+        Some("trace") => record_trace(&args[2..]),
+        Some("trace-diff") => diff_traces(&args[2..]),
+        Some("run") => run(&args[2..]),
+        Some("bench") => run_bench(&args[2..]),
+        None => run(&[String::from("--all")]),
+        Some(command) => {
+            eprintln!("unknown command '{}'", command);
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+const USAGE: &str = "usage: aoc2019 [<command> [<args>]]
+
+commands:
+    run (--all | --day <n>) [...]      solve puzzles, `run --all` when no command is given
+    bench [...] | bench --intcode      time the solvers or the Intcode interpreter
+    asm <source>                       assemble an Intcode program
+    disasm <program>                   list an Intcode program as assembly
+    debug <program> [--script <file>]  step through an Intcode program
+    trace <program> [input]...         print the execution trace of a program
+    trace-diff <trace> <trace>         find where two traces diverge
+    ascii <program>                    run an ASCII program on the terminal";
+
+fn run_ascii(program_file: Option<&String>) {
+    let program_file = program_file.unwrap_or_else(|| {
+        eprintln!("usage: aoc2019 ascii <program>");
+        process::exit(2);
+    });
+    let machine = interpreter::IntcodeInterpreter::from_file(program_file).unwrap_or_else(|e| {
+        eprintln!("{}: {}", program_file, e);
+        process::exit(1);
+    });
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let mut machine = machine.with_io(intcode_io::AsciiIo::new(stdin.lock(), stdout.lock()));
+    match machine.execute() {
+        Ok(interpreter::ExecutionState::Suspended) => eprintln!("input closed, program suspended"),
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("{}: {}", program_file, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn assemble(source_file: Option<&String>) {
+    let source_file = source_file.unwrap_or_else(|| {
+        eprintln!("usage: aoc2019 asm <source>");
+        process::exit(2);
+    });
+    let program = fs::read_to_string(source_file)
+        .map_err(|e| e.to_string())
+        .and_then(|source| assembler::assemble(&source).map_err(|e| e.to_string()));
+    match program {
+        Ok(program) => {
+            let words: Vec<String> = program.iter().map(i128::to_string).collect();
+            println!("{}", words.join(","));
+        }
+        Err(e) => {
+            eprintln!("{}: {}", source_file, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn debug(args: &[String]) {
+    let (program_file, script) = match args {
+        [program] => (program, None),
+        [program, flag, script] if flag == "--script" => (program, Some(script)),
+        _ => {
+            eprintln!("usage: aoc2019 debug <program> [--script <file>]");
+            process::exit(2);
+        }
+    };
+    let machine = interpreter::IntcodeInterpreter::from_file(program_file).unwrap_or_else(|e| {
+        eprintln!("{}: {}", program_file, e);
+        process::exit(1);
+    });
+
+    let mut debugger = debugger::Debugger::new(machine);
+    let stdout = io::stdout();
+    let result = match script {
+        Some(script) => match File::open(script) {
+            Ok(file) => debugger.run(BufReader::new(file), &mut stdout.lock(), true),
+            Err(e) => Err(e),
+        },
+        None => debugger.run(io::stdin().lock(), &mut stdout.lock(), false),
+    };
+    if let Err(e) = result {
+        eprintln!("debug session failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn disassemble(program_file: Option<&String>) {
+    let program_file = program_file.unwrap_or_else(|| {
+        eprintln!("usage: aoc2019 disasm <program>");
+        process::exit(2);
+    });
+    match interpreter::read_program(program_file) {
+        Ok(program) => print!("{}", disassembler::listing(&program)),
+        Err(e) => {
+            eprintln!("{}: {}", program_file, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn record_trace(args: &[String]) {
+    let (program_file, inputs) = match args.split_first() {
+        Some(split) => split,
+        None => {
+            eprintln!("usage: aoc2019 trace <program> [input]...");
+            process::exit(2);
+        }
+    };
+    let run = || -> Result<(), Box<dyn std::error::Error>> {
+        let mut machine = interpreter::IntcodeInterpreter::from_file(program_file)?;
+        for input in inputs {
+            machine.set_input(input.parse::<i128>()?);
+        }
+        let stdout = io::stdout();
+        let mut sink = trace::TraceWriter::new(BufWriter::new(stdout.lock()));
+        let result = machine.execute_traced(&mut sink);
+        sink.into_inner().flush()?;
+        result?;
+        Ok(())
+    };
+    if let Err(e) = run() {
+        eprintln!("{}: {}", program_file, e);
+        process::exit(1);
+    }
+}
+
+fn diff_traces(args: &[String]) {
+    let (file_a, file_b) = match args {
+        [a, b] => (a, b),
+        _ => {
+            eprintln!("usage: aoc2019 trace-diff <trace> <trace>");
+            process::exit(2);
+        }
+    };
+    let read = |file: &String| -> Result<Vec<trace::TraceEntry>, Box<dyn std::error::Error>> {
+        trace::read_trace(BufReader::new(File::open(file)?))
+    };
+    let (trace_a, trace_b) = match (read(file_a), read(file_b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("failed to read trace: {}", e);
+            process::exit(2);
+        }
+    };
+
+    match trace::first_divergence(&trace_a, &trace_b) {
+        None => println!("traces are identical ({} entries)", trace_a.len()),
+        Some(index) => {
+            println!("traces diverge at entry {}", index);
+            let show = |entry: Option<&trace::TraceEntry>| {
+                entry.map_or(String::from("<end of trace>"), |e| e.to_string())
+            };
+            println!("< {}", show(trace_a.get(index)));
+            println!("> {}", show(trace_b.get(index)));
+            process::exit(1);
+        }
+    }
+}
+
+const RUN_USAGE: &str = "usage: aoc2019 run (--all | --day <n> [--part 1|2] [--input <file>])
+                   [--target <value>] [--range <from>-<until>] [--workers <n>]
+                   [--answers <file>]";
+
+/// Options of the `run` command.
+struct RunOptions {
+    days: Vec<u32>,
+    parts: Vec<u32>,
+    input: Option<String>,
+    parameters: Parameters,
+    answers: Option<ExpectedAnswers>,
+}
+
+fn parse_run_options(args: &[String]) -> Result<RunOptions, String> {
+    let mut all = false;
+    let mut day = None;
+    let mut part = None;
+    let mut options = RunOptions {
+        days: Vec::new(),
+        parts: vec![1, 2],
+        input: None,
+        parameters: Parameters::default(),
+        answers: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("{} requires a value", flag))
+        };
+        match flag.as_str() {
+            "--all" => all = true,
+            "--day" => day = Some(parse_value::<u32>(flag, value()?)?),
+            "--part" => part = Some(parse_value::<u32>(flag, value()?)?),
+            "--input" => options.input = Some(value()?.clone()),
+            "--target" => options.parameters.target = parse_value(flag, value()?)?,
+            "--range" => {
+                let range = value()?;
+                let (from, until) = range
+                    .split_once('-')
+                    .ok_or_else(|| format!("invalid value '{}' for {}", range, flag))?;
+                options.parameters.range =
+                    Some((parse_value(flag, from)?, parse_value(flag, until)?));
+            }
+            "--workers" => options.parameters.workers = parse_value(flag, value()?)?,
+            "--answers" => {
+                let answers_file = value()?;
+                let answers = ExpectedAnswers::from_file(answers_file)
+                    .map_err(|e| format!("{}: {}", answers_file, e))?;
+                options.answers = Some(answers);
+            }
+            _ => return Err(format!("unknown option '{}'", flag)),
+        }
+    }
+
+    options.days = match (all, day) {
+        (true, None) => (1..=SOLVERS.len() as u32).collect(),
+        (false, Some(day)) if solver::solver(day).is_some() => vec![day],
+        (false, Some(day)) => return Err(format!("no solver for day {}", day)),
+        _ => return Err(String::from("expected either --all or --day")),
+    };
+    match part {
+        None => {}
+        Some(part @ 1..=2) => options.parts = vec![part],
+        Some(part) => return Err(format!("no part {}", part)),
+    }
+    if options.input.is_some() && all {
+        return Err(String::from("--input requires --day"));
+    }
+    Ok(options)
+}
+
+const BENCH_USAGE: &str = "usage: aoc2019 bench [--day <n>] [--runs <n>] [--csv]
+                     [--baseline <file>] [--threshold <percent>]
+       aoc2019 bench --intcode";
+
+/// Intcode programs timed by `bench --intcode`: name, program file and
+/// inputs.
+const INTCODE_WORKLOADS: [(&str, &str, &[i128]); 3] = [
+    ("day05 pt1", "./data/day05.txt", &[1]),
+    ("day05 pt2", "./data/day05.txt", &[5]),
+    ("day07 amp", "./data/day07.txt", &[4, 1234]),
+];
+
+/// Minimum time spent on each Intcode measurement.
+const INTCODE_BENCH_TIME: Duration = Duration::from_secs(1);
+
+/// Times parsing and both parts of every selected day. Exits with status
+/// 1 when a solver fails or gets slower than the baseline.
+fn run_bench(args: &[String]) {
+    let mut days: Vec<u32> = (1..=SOLVERS.len() as u32).collect();
+    let mut runs = 10;
+    let mut csv = false;
+    let mut baseline = None;
+    let mut threshold = 10.0;
+    let mut intcode = false;
+    let mut parse_args = || -> Result<(), String> {
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("{} requires a value", flag))
+            };
+            match flag.as_str() {
+                "--day" => {
+                    let day = parse_value(flag, value()?)?;
+                    if solver::solver(day).is_none() {
+                        return Err(format!("no solver for day {}", day));
+                    }
+                    days = vec![day];
+                }
+                "--runs" => runs = parse_value(flag, value()?)?,
+                "--csv" => csv = true,
+                "--baseline" => {
+                    let baseline_file = value()?;
+                    let read = bench::Baseline::from_file(baseline_file)
+                        .map_err(|e| format!("{}: {}", baseline_file, e))?;
+                    baseline = Some(read);
+                }
+                "--threshold" => threshold = parse_value(flag, value()?)?,
+                "--intcode" => intcode = true,
+                _ => return Err(format!("unknown option '{}'", flag)),
+            }
+        }
+        Ok(())
+    };
+    let mut result = parse_args();
+    if result.is_ok() && intcode && args.len() > 1 {
+        result = Err(String::from("--intcode takes no other options"));
+    }
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        eprintln!("{}", BENCH_USAGE);
+        process::exit(2);
+    }
+    if intcode {
+        bench_intcode();
+        return;
+    }
+
+    let parameters = Parameters::default();
+    let mut timings = Vec::new();
+    let mut failed = false;
+    for day in days {
+        let puzzle = solver::solver(day).expect("Solver of selected day");
+        let input_file = format!("./data/day{:02}.txt", day);
+        let result = fs::read_to_string(&input_file)
+            .map_err(AocError::from)
+            .and_then(|text| bench::time_solver(day, puzzle, &text, runs, &parameters));
+        match result {
+            Ok(day_timings) => timings.extend(day_timings),
+            Err(e) => {
+                eprintln!("Day {:02}: {}", day, e);
+                failed = true;
+            }
+        }
+    }
+
+    let stdout = io::stdout();
+    let result = if csv {
+        bench::write_csv(&timings, &mut stdout.lock())
+    } else {
+        bench::write_table(&timings, baseline.as_ref(), threshold, &mut stdout.lock())
+    };
+    if let Err(e) = result {
+        eprintln!("failed to write report: {}", e);
+        process::exit(1);
+    }
+
+    if let Some(baseline) = &baseline {
+        for (timing, change) in bench::slowdowns(&timings, baseline, threshold) {
+            eprintln!(
+                "Day {:02} {}: median {:.1}% slower than baseline",
+                timing.day,
+                timing.phase.name(),
+                change
+            );
+            failed = true;
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Times the Intcode interpreter alone on the workloads and the day 2
+/// search.
+fn bench_intcode() {
+    let run = || -> Result<(), AocError> {
+        let mut rates = Vec::new();
+        for &(name, program_file, inputs) in &INTCODE_WORKLOADS {
+            let template = interpreter::IntcodeInterpreter::from_file(program_file)?;
+            rates.push(bench::time_intcode(
+                name,
+                &template,
+                inputs,
+                INTCODE_BENCH_TIME,
+            )?);
+        }
+        let template = interpreter::IntcodeInterpreter::from_file("./data/day02.txt")?;
+        let search = bench::time_search(&template, INTCODE_BENCH_TIME)?;
+        bench::write_intcode_table(&rates, &search, &mut io::stdout().lock())?;
+        Ok(())
+    };
+    if let Err(e) = run() {
+        eprintln!("Intcode benchmark failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{}' for {}", value, flag))
+}
+
+fn run(args: &[String]) {
+    let options = parse_run_options(args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!("{}", RUN_USAGE);
+        process::exit(2);
+    });
+
+    if options.days.len() > 1 {
+        println!("Hello, Advent of Code 2019!");
+    }
+    let mut failed = false;
+    for &day in &options.days {
+        let puzzle = solver::solver(day).expect("Solver of selected day");
+        let input_file = match &options.input {
+            Some(input) => input.clone(),
+            None => format!("./data/day{:02}.txt", day),
+        };
+        let input = match puzzle.read_input(&input_file) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Day {:02}: {}: {}", day, input_file, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        for &part in &options.parts {
+            let solution = match puzzle.solve(&input, part, &options.parameters) {
+                Ok(solution) => solution,
+                Err(e) => {
+                    eprintln!("Day {:02}.{}: {}", day, part, e);
+                    failed = true;
+                    continue;
+                }
+            };
+            println!("Day {:02}.{}: {}", day, part, solution.message);
+            if let Some(Verdict::Wrong { expected }) = options
+                .answers
+                .as_ref()
+                .map(|a| a.check(day, part, &solution.answer))
+            {
+                eprintln!(
+                    "Day {:02}.{}: wrong answer, expected {}",
+                    day, part, expected
+                );
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
 }
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const PAGE_BITS: usize = 6;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+const PAGE_MASK: usize = PAGE_SIZE - 1;
+
+type Page = [i128; PAGE_SIZE];
+
+/// Copy-on-write Intcode memory.
+///
+/// The loaded program is kept as a base image shared by all clones. An
+/// instance copies the image on its first write to it, so the program's
+/// own words are then read and written as a flat vector. Words past the
+/// image live in a sparse page table which clones share, along with its
+/// pages, until one of them writes there again. Forking a machine that
+/// has not written to its image therefore copies no memory, however high
+/// the addresses it used. Addresses never written read as zero past the
+/// base image.
+#[derive(Clone, Debug)]
+pub struct Memory {
+    base: Arc<[i128]>,
+    /// Private copy of the base image, empty until the first write to it.
+    words: Vec<i128>,
+    /// Pages past the base image, keyed by page number.
+    pages: Arc<BTreeMap<usize, Arc<Page>>>,
+    len: usize,
+}
+
+impl Memory {
+    pub fn new(image: &[i128]) -> Self {
+        Memory {
+            base: Arc::from(image),
+            words: Vec::new(),
+            pages: Arc::new(BTreeMap::new()),
+            len: image.len(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, address: usize) -> i128 {
+        match self.words.get(address) {
+            Some(&value) => value,
+            None => self.get_unowned(address),
+        }
+    }
+
+    #[inline]
+    pub fn set(&mut self, address: usize, value: i128) {
+        match self.words.get_mut(address) {
+            Some(word) => *word = value,
+            None => self.set_unowned(address, value),
+        }
+    }
+
+    /// Returns contents up to the highest address holding a value from
+    /// the image or a write.
+    pub fn to_vec(&self) -> Vec<i128> {
+        (0..self.len).map(|address| self.get(address)).collect()
+    }
+
+    // Kept out of line so the flat fast paths above stay small enough to
+    // inline into the interpreter.
+    #[cold]
+    #[inline(never)]
+    fn get_unowned(&self, address: usize) -> i128 {
+        if let Some(&value) = self.base.get(address) {
+            return value;
+        }
+        self.pages
+            .get(&(address >> PAGE_BITS))
+            .map_or(0, |page| page[address & PAGE_MASK])
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn set_unowned(&mut self, address: usize, value: i128) {
+        if address < self.base.len() {
+            self.words = self.base.to_vec();
+            self.words[address] = value;
+            return;
+        }
+        let page = Arc::make_mut(&mut self.pages)
+            .entry(address >> PAGE_BITS)
+            .or_insert_with(|| Arc::new([0; PAGE_SIZE]));
+        Arc::make_mut(page)[address & PAGE_MASK] = value;
+        self.len = self.len.max(address + 1);
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::panic;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use super::intcode_io::{IntcodeIo, QueueIo};
+use super::interpreter::{BreakCause, ExecutionState, IntcodeError, IntcodeInterpreter};
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum NetworkState {
+    /// Every machine halted.
+    Finished,
+    /// No machine can make progress; `waiting` lists machines suspended
+    /// on input that will never arrive. Queuing input resumes the network.
+    Deadlock { waiting: Vec<usize> },
+    /// Machine `machine` stopped at a breakpoint or watchpoint.
+    Breakpoint { machine: usize, cause: BreakCause },
+}
+
+#[derive(Debug)]
+pub enum NetworkError {
+    InvalidLink {
+        from: usize,
+        to: usize,
+        machines: usize,
+    },
+    Machine {
+        machine: usize,
+        error: IntcodeError,
+    },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::InvalidLink { from, to, machines } => write!(
+                f,
+                "link {} -> {} refers to machine outside of network of {}",
+                from, to, machines
+            ),
+            NetworkError::Machine { machine, error } => write!(f, "machine {}: {}", machine, error),
+        }
+    }
+}
+
+impl Error for NetworkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetworkError::Machine { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Group of linked Intcode machines, run either in turn on the current
+/// thread with `run` or each on its own thread with `run_parallel`.
+///
+/// Output of a machine is copied to the input of every machine it is
+/// linked to. Output of machines without outgoing links is collected by
+/// the network and read with `get_output`.
+pub struct Network {
+    machines: Vec<IntcodeInterpreter>,
+    links: Vec<Vec<usize>>,
+    last_outputs: Vec<Option<i128>>,
+    output: VecDeque<(usize, i128)>,
+}
+
+impl Network {
+    /// Creates network of unconnected `machines`.
+    pub fn new(machines: Vec<IntcodeInterpreter>) -> Self {
+        let count = machines.len();
+        Network {
+            machines,
+            links: vec![Vec::new(); count],
+            last_outputs: vec![None; count],
+            output: VecDeque::new(),
+        }
+    }
+
+    /// Connects `machines` one after another; output of the last machine
+    /// is left to the caller.
+    pub fn chain(machines: Vec<IntcodeInterpreter>) -> Self {
+        let mut network = Network::new(machines);
+        for index in 1..network.machines.len() {
+            network.links[index - 1].push(index);
+        }
+        network
+    }
+
+    /// Connects `machines` one after another, feeding output of the last
+    /// machine back to the first one.
+    pub fn ring(machines: Vec<IntcodeInterpreter>) -> Self {
+        let mut network = Network::chain(machines);
+        if let Some(last) = network.links.len().checked_sub(1) {
+            network.links[last].push(0);
+        }
+        network
+    }
+
+    /// Adds directed link sending output of machine `from` to input of
+    /// machine `to`.
+    pub fn connect(&mut self, from: usize, to: usize) -> Result<(), NetworkError> {
+        let machines = self.machines.len();
+        if from >= machines || to >= machines {
+            return Err(NetworkError::InvalidLink { from, to, machines });
+        }
+        if !self.links[from].contains(&to) {
+            self.links[from].push(to);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    pub fn machine(&self, index: usize) -> Option<&IntcodeInterpreter> {
+        self.machines.get(index)
+    }
+
+    pub fn machine_mut(&mut self, index: usize) -> Option<&mut IntcodeInterpreter> {
+        self.machines.get_mut(index)
+    }
+
+    /// Queues `value` as input of machine `index`.
+    pub fn set_input(&mut self, index: usize, value: i128) {
+        self.machines[index].set_input(value);
+    }
+
+    /// Returns output of a machine without outgoing links, together with
+    /// the index of the machine that produced it.
+    pub fn get_output(&mut self) -> Option<(usize, i128)> {
+        self.output.pop_front()
+    }
+
+    /// Returns the most recent value written by machine `index`.
+    pub fn last_output(&self, index: usize) -> Option<i128> {
+        self.last_outputs.get(index).copied().flatten()
+    }
+
+    /// Runs machines in turn until all of them halt, none of them can make
+    /// progress or one hits a breakpoint.
+    pub fn run(&mut self) -> Result<NetworkState, NetworkError> {
+        loop {
+            let mut progress = false;
+            for index in 0..self.machines.len() {
+                if *self.machines[index].get_state() == ExecutionState::Finished {
+                    continue;
+                }
+
+                let machine = &mut self.machines[index];
+                let steps = machine.get_instruction_count();
+                let state = machine.execute().map_err(|error| NetworkError::Machine {
+                    machine: index,
+                    error,
+                })?;
+                progress |= machine.get_instruction_count() != steps;
+                self.route_output(index);
+
+                if let ExecutionState::Breakpoint(cause) = state {
+                    return Ok(NetworkState::Breakpoint {
+                        machine: index,
+                        cause,
+                    });
+                }
+            }
+
+            if !progress {
+                return Ok(self.settled_state());
+            }
+        }
+    }
+
+    /// Runs every machine on its own thread until all of them halt or
+    /// block for good. Input and output travel over channels, with values
+    /// routed along the links by the calling thread.
+    ///
+    /// Once all machines feeding a machine have stopped, its input channel
+    /// is closed, so a machine halting releases the ones blocked reading
+    /// its output. When every running machine is blocked and no value is
+    /// in flight the network is deadlocked and all channels are closed.
+    /// Machines stopped this way remain suspended on input and can be
+    /// resumed by both `run` and `run_parallel`.
+    pub fn run_parallel(&mut self) -> Result<NetworkState, NetworkError> {
+        let count = self.machines.len();
+        let mut sources = vec![Vec::new(); count];
+        for (index, targets) in self.links.iter().enumerate() {
+            for &target in targets {
+                sources[target].push(index);
+            }
+        }
+        for index in 0..count {
+            self.route_output(index);
+        }
+
+        let (events, event_queue) = mpsc::channel();
+        let mut inputs = Vec::with_capacity(count);
+        let mut sent = vec![0u64; count];
+        let mut threads = Vec::with_capacity(count);
+        for (index, machine) in self.machines.drain(..).enumerate() {
+            let (input, receiver) = mpsc::channel();
+            let io = ThreadIo {
+                machine: index,
+                input: receiver,
+                events: events.clone(),
+                consumed: 0,
+            };
+            let (mut machine, queue) = machine.replace_io(io);
+            for value in queue.input {
+                let _ = input.send(value);
+                sent[index] += 1;
+            }
+            inputs.push(Some(input));
+
+            threads.push(thread::spawn(move || {
+                let result = machine.execute();
+                let (machine, io) = machine.replace_io(QueueIo::default());
+                let _ = io.events.send(Event::Stopped {
+                    machine: index,
+                    result,
+                });
+                (machine, io.input)
+            }));
+        }
+        drop(events);
+
+        let mut stopped = vec![false; count];
+        let mut waiting = vec![None; count];
+        let mut undelivered = vec![Vec::new(); count];
+        let mut error = None;
+        let mut running = count;
+        close_finished_inputs(&mut inputs, &sources, &stopped);
+        while running > 0 {
+            let event = match event_queue.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            };
+            match event {
+                Event::Output { machine, value } => {
+                    self.record_output(machine, value);
+                    for &target in &self.links[machine] {
+                        match &inputs[target] {
+                            Some(input) if input.send(value).is_ok() => sent[target] += 1,
+                            _ => undelivered[target].push(value),
+                        }
+                    }
+                }
+                Event::Waiting { machine, consumed } => waiting[machine] = Some(consumed),
+                Event::Stopped { machine, result } => {
+                    running -= 1;
+                    stopped[machine] = true;
+                    if let Err(machine_error) = result {
+                        error.get_or_insert(NetworkError::Machine {
+                            machine,
+                            error: machine_error,
+                        });
+                        inputs.iter_mut().for_each(|input| *input = None);
+                    }
+                    close_finished_inputs(&mut inputs, &sources, &stopped);
+                }
+            }
+
+            let deadlock =
+                (0..count).all(|index| stopped[index] || waiting[index] == Some(sent[index]));
+            if running > 0 && deadlock {
+                inputs.iter_mut().for_each(|input| *input = None);
+            }
+        }
+        drop(inputs);
+
+        for (index, thread) in threads.into_iter().enumerate() {
+            let (mut machine, input) = thread
+                .join()
+                .unwrap_or_else(|payload| panic::resume_unwind(payload));
+            for value in input.try_iter().chain(undelivered[index].drain(..)) {
+                machine.set_input(value);
+            }
+            self.machines.push(machine);
+        }
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(self.settled_state()),
+        }
+    }
+
+    /// State of a network where no machine can run any further.
+    fn settled_state(&self) -> NetworkState {
+        for (index, machine) in self.machines.iter().enumerate() {
+            if let ExecutionState::Breakpoint(cause) = machine.get_state() {
+                return NetworkState::Breakpoint {
+                    machine: index,
+                    cause: *cause,
+                };
+            }
+        }
+        let waiting: Vec<usize> = (0..self.machines.len())
+            .filter(|&index| *self.machines[index].get_state() != ExecutionState::Finished)
+            .collect();
+        if waiting.is_empty() {
+            NetworkState::Finished
+        } else {
+            NetworkState::Deadlock { waiting }
+        }
+    }
+
+    fn route_output(&mut self, index: usize) {
+        while let Some(value) = self.machines[index].get_output() {
+            self.record_output(index, value);
+            for &target in &self.links[index] {
+                self.machines[target].set_input(value);
+            }
+        }
+    }
+
+    fn record_output(&mut self, index: usize, value: i128) {
+        self.last_outputs[index] = Some(value);
+        if self.links[index].is_empty() {
+            self.output.push_back((index, value));
+        }
+    }
+}
+
+/// Closes input of machines that will get no further input because all
+/// machines linked to them have stopped.
+fn close_finished_inputs(
+    inputs: &mut [Option<Sender<i128>>],
+    sources: &[Vec<usize>],
+    stopped: &[bool],
+) {
+    for (input, sources) in inputs.iter_mut().zip(sources) {
+        if sources.iter().all(|&source| stopped[source]) {
+            *input = None;
+        }
+    }
+}
+
+enum Event {
+    Output {
+        machine: usize,
+        value: i128,
+    },
+    /// Machine is about to block for input after reading `consumed` values.
+    Waiting {
+        machine: usize,
+        consumed: u64,
+    },
+    Stopped {
+        machine: usize,
+        result: Result<ExecutionState, IntcodeError>,
+    },
+}
+
+/// Device of a machine run by `Network::run_parallel`. Reads block on the
+/// input channel, reporting to the scheduler before blocking; writes are
+/// sent to the scheduler for routing.
+struct ThreadIo {
+    machine: usize,
+    input: Receiver<i128>,
+    events: Sender<Event>,
+    consumed: u64,
+}
+
+impl IntcodeIo for ThreadIo {
+    fn read(&mut self) -> Option<i128> {
+        let value = match self.input.try_recv() {
+            Ok(value) => Some(value),
+            Err(TryRecvError::Disconnected) => None,
+            Err(TryRecvError::Empty) => {
+                let _ = self.events.send(Event::Waiting {
+                    machine: self.machine,
+                    consumed: self.consumed,
+                });
+                self.input.recv().ok()
+            }
+        };
+        if value.is_some() {
+            self.consumed += 1;
+        }
+        value
+    }
+
+    fn write(&mut self, value: i128) {
+        let _ = self.events.send(Event::Output {
+            machine: self.machine,
+            value,
+        });
+    }
+}
@@ -0,0 +1,249 @@
+/// Order in which `Permutations` yields items.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Order {
+    /// Lexicographic order of item positions, starting with the items as
+    /// given. Sorted input gives permutations in ascending order.
+    Lexicographic,
+    /// Heap's algorithm; consecutive permutations differ by a single swap.
+    Heap,
+}
+
+/// Iterator over all permutations of a list of items.
+///
+/// Items are permuted by position, so a list with repeated values still
+/// yields n! permutations, some of them equal.
+#[derive(Clone, Debug)]
+pub struct Permutations<T> {
+    items: Vec<T>,
+    order: Order,
+    /// Lexicographic: positions of items in the current permutation.
+    /// Heap: per position swap counters of the iterative algorithm.
+    state: Vec<usize>,
+    index: usize,
+    started: bool,
+    done: bool,
+    /// Number of permutations left, `None` when it doesn't fit in `usize`.
+    remaining: Option<usize>,
+}
+
+impl<T: Clone> Permutations<T> {
+    pub fn new(items: &[T], order: Order) -> Self {
+        let state = match order {
+            Order::Lexicographic => (0..items.len()).collect(),
+            Order::Heap => vec![0; items.len()],
+        };
+        Permutations {
+            items: items.to_vec(),
+            order,
+            state,
+            index: 0,
+            started: false,
+            done: false,
+            remaining: (1..=items.len()).try_fold(1usize, |count, n| count.checked_mul(n)),
+        }
+    }
+
+    pub fn lexicographic(items: &[T]) -> Self {
+        Permutations::new(items, Order::Lexicographic)
+    }
+
+    pub fn heap(items: &[T]) -> Self {
+        Permutations::new(items, Order::Heap)
+    }
+
+    fn current(&self) -> Vec<T> {
+        match self.order {
+            Order::Lexicographic => self.state.iter().map(|&i| self.items[i].clone()).collect(),
+            Order::Heap => self.items.clone(),
+        }
+    }
+
+    /// Moves to the next permutation in lexicographic order of positions.
+    /// Returns `false` after the last one.
+    fn advance_lexicographic(&mut self) -> bool {
+        let positions = &mut self.state;
+        let pivot = match (1..positions.len())
+            .rev()
+            .find(|&i| positions[i - 1] < positions[i])
+        {
+            Some(i) => i - 1,
+            None => return false,
+        };
+        // positions after the pivot are descending and the first of them
+        // is larger than the pivot
+        let successor = (pivot + 1..positions.len())
+            .rev()
+            .find(|&i| positions[pivot] < positions[i])
+            .unwrap_or(pivot + 1);
+        positions.swap(pivot, successor);
+        positions[pivot + 1..].reverse();
+        true
+    }
+
+    /// Moves to the next permutation generated by Heap's algorithm.
+    /// Returns `false` after the last one.
+    fn advance_heap(&mut self) -> bool {
+        while self.index < self.items.len() {
+            if self.state[self.index] < self.index {
+                if self.index.is_multiple_of(2) {
+                    self.items.swap(0, self.index);
+                } else {
+                    self.items.swap(self.state[self.index], self.index);
+                }
+
+                self.state[self.index] += 1;
+                self.index = 0;
+                return true;
+            } else {
+                self.state[self.index] = 0;
+                self.index += 1;
+            }
+        }
+        false
+    }
+}
+
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.started {
+            let advanced = match self.order {
+                Order::Lexicographic => self.advance_lexicographic(),
+                Order::Heap => self.advance_heap(),
+            };
+            if !advanced {
+                self.done = true;
+                return None;
+            }
+        }
+        self.started = true;
+        self.remaining = self.remaining.map(|remaining| remaining - 1);
+        Some(self.current())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            _ if self.done => (0, Some(0)),
+            Some(remaining) => (remaining, Some(remaining)),
+            None => (usize::MAX, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const ORDERS: [Order; 2] = [Order::Lexicographic, Order::Heap];
+
+    fn factorial(n: usize) -> usize {
+        (1..=n).product()
+    }
+
+    #[test]
+    fn yields_n_factorial_distinct_permutations() {
+        for order in ORDERS {
+            for n in 0..=7 {
+                let items: Vec<usize> = (0..n).collect();
+                let permutations: Vec<Vec<usize>> = Permutations::new(&items, order).collect();
+                let distinct: HashSet<&Vec<usize>> = permutations.iter().collect();
+                assert_eq!(permutations.len(), factorial(n), "{:?} n={}", order, n);
+                assert_eq!(distinct.len(), factorial(n), "{:?} n={}", order, n);
+                for permutation in &permutations {
+                    let mut sorted = permutation.clone();
+                    sorted.sort_unstable();
+                    assert_eq!(sorted, items, "{:?} n={}", order, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn starts_with_items_as_given() {
+        for order in ORDERS {
+            let items = ['c', 'a', 'b'];
+            let first = Permutations::new(&items, order).next();
+            assert_eq!(first, Some(items.to_vec()), "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn size_hint_is_exact() {
+        for order in ORDERS {
+            for n in 0..=6 {
+                let items: Vec<usize> = (0..n).collect();
+                let mut permutations = Permutations::new(&items, order);
+                let mut remaining = factorial(n);
+                loop {
+                    assert_eq!(permutations.size_hint(), (remaining, Some(remaining)));
+                    if permutations.next().is_none() {
+                        break;
+                    }
+                    remaining -= 1;
+                }
+                assert_eq!(remaining, 0, "{:?} n={}", order, n);
+                assert_eq!(permutations.next(), None);
+                assert_eq!(permutations.size_hint(), (0, Some(0)));
+            }
+        }
+    }
+
+    #[test]
+    fn size_hint_without_upper_bound_on_overflow() {
+        let items: Vec<usize> = (0..25).collect();
+        assert_eq!(Permutations::heap(&items).size_hint(), (usize::MAX, None));
+    }
+
+    #[test]
+    fn lexicographic_order_is_ascending() {
+        for n in 0..=6 {
+            let items: Vec<usize> = (0..n).collect();
+            let permutations: Vec<Vec<usize>> = Permutations::lexicographic(&items).collect();
+            assert!(
+                permutations.windows(2).all(|pair| pair[0] < pair[1]),
+                "n={}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn heap_order_swaps_one_pair_at_a_time() {
+        for n in 2..=6 {
+            let items: Vec<usize> = (0..n).collect();
+            let permutations: Vec<Vec<usize>> = Permutations::heap(&items).collect();
+            for pair in permutations.windows(2) {
+                let changed = pair[0].iter().zip(&pair[1]).filter(|(a, b)| a != b).count();
+                assert_eq!(changed, 2, "n={} {:?}", n, pair);
+            }
+        }
+    }
+
+    #[test]
+    fn heap_order_matches_algorithm() {
+        let permutations: Vec<Vec<i32>> = Permutations::heap(&[1, 2, 3]).collect();
+        assert_eq!(
+            permutations,
+            vec![
+                vec![1, 2, 3],
+                vec![2, 1, 3],
+                vec![3, 1, 2],
+                vec![1, 3, 2],
+                vec![2, 3, 1],
+                vec![3, 2, 1],
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_items_are_permuted_by_position() {
+        for order in ORDERS {
+            assert_eq!(Permutations::new(&[7, 7, 7], order).count(), 6);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::any::Any;
+use std::fmt;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::thread;
+
+use super::error::AocError;
+use super::{day01, day02, day03, day04, day05, day06, day07, day08};
+
+/// Solvers of all days, solver of day N at index N - 1.
+pub static SOLVERS: [&dyn Puzzle; 8] = [
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+];
+
+/// Returns solver of puzzle published on `day`.
+pub fn solver(day: u32) -> Option<&'static dyn Puzzle> {
+    let index = (day as usize).checked_sub(1)?;
+    SOLVERS.get(index).copied()
+}
+
+/// Settings of solvers besides the puzzle input.
+#[derive(Clone, Debug)]
+pub struct Parameters {
+    /// Day 02: value the program has to leave at address 0.
+    pub target: i32,
+    /// Day 04: password range used in place of the puzzle input.
+    pub range: Option<(u32, u32)>,
+    /// Threads a solver may use.
+    pub workers: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters {
+            target: 19690720,
+            range: None,
+            workers: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+}
+
+/// Solution of a puzzle part.
+#[derive(Clone, Debug)]
+pub struct Solution {
+    /// Answer as entered on the puzzle page.
+    pub answer: String,
+    /// Sentence describing the answer.
+    pub message: String,
+}
+
+/// Puzzle of a single day. The input is parsed once and shared by both
+/// parts.
+pub trait Solver {
+    type Input;
+    type Answer1: fmt::Display;
+    type Answer2: fmt::Display;
+
+    fn parse(&self, text: &str) -> Result<Self::Input, AocError>;
+    fn part1(
+        &self,
+        input: &Self::Input,
+        parameters: &Parameters,
+    ) -> Result<Self::Answer1, AocError>;
+    fn part2(
+        &self,
+        input: &Self::Input,
+        parameters: &Parameters,
+    ) -> Result<Self::Answer2, AocError>;
+
+    fn describe_part1(&self, answer: &Self::Answer1) -> String {
+        answer.to_string()
+    }
+
+    fn describe_part2(&self, answer: &Self::Answer2) -> String {
+        answer.to_string()
+    }
+}
+
+/// Parsed input of a `Puzzle`.
+pub type PuzzleInput = Box<dyn Any + Send>;
+
+/// `Solver` with input and answers erased, so solvers of all days fit in
+/// one table.
+pub trait Puzzle: Sync {
+    fn parse(&self, text: &str) -> Result<PuzzleInput, AocError>;
+    /// Solves `part` of the puzzle for `input` returned by `parse` of the
+    /// same puzzle. Input of another puzzle is rejected as invalid.
+    fn solve(
+        &self,
+        input: &PuzzleInput,
+        part: u32,
+        parameters: &Parameters,
+    ) -> Result<Solution, AocError>;
+
+    fn read_input(&self, input_file: &str) -> Result<PuzzleInput, AocError> {
+        self.parse(&fs::read_to_string(input_file)?)
+    }
+}
+
+impl<S> Puzzle for S
+where
+    S: Solver + Sync,
+    S::Input: Send + 'static,
+{
+    fn parse(&self, text: &str) -> Result<PuzzleInput, AocError> {
+        Ok(Box::new(Solver::parse(self, text)?))
+    }
+
+    fn solve(
+        &self,
+        input: &PuzzleInput,
+        part: u32,
+        parameters: &Parameters,
+    ) -> Result<Solution, AocError> {
+        let input = input.downcast_ref::<S::Input>().ok_or_else(|| {
+            AocError::InvalidInput(String::from("input was parsed by another puzzle"))
+        })?;
+        match part {
+            1 => {
+                let answer = self.part1(input, parameters)?;
+                Ok(Solution {
+                    answer: answer.to_string(),
+                    message: self.describe_part1(&answer),
+                })
+            }
+            2 => {
+                let answer = self.part2(input, parameters)?;
+                Ok(Solution {
+                    answer: answer.to_string(),
+                    message: self.describe_part2(&answer),
+                })
+            }
+            _ => Err(AocError::NoAnswer(format!("no part {}", part))),
+        }
+    }
+}
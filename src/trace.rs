@@ -0,0 +1,208 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use super::instruction::Instruction;
+
+/// Record of a single executed instruction.
+///
+/// Written as one line: pointer, mnemonic, instruction word, operand
+/// values and the memory write, e.g. `2 ADD 1001 [41,1,10] [10=42]`.
+/// Read operands hold the value read, write operands the target address;
+/// operands that couldn't be resolved are written as `?`. Like
+/// `Instruction::modes`, only the instruction's own arguments are used.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TraceEntry {
+    pub instr_ptr: usize,
+    pub instruction: Instruction,
+    pub operands: [Option<i128>; 3],
+    pub write: Option<(usize, i128)>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operands: Vec<String> = self
+            .operands
+            .iter()
+            .take(self.instruction.opcode.arg_count())
+            .map(|o| o.map_or(String::from("?"), |v| v.to_string()))
+            .collect();
+        write!(
+            f,
+            "{} {} {} [{}] [",
+            self.instr_ptr,
+            self.instruction.opcode.mnemonic(),
+            self.instruction.encode(),
+            operands.join(",")
+        )?;
+        if let Some((address, value)) = self.write {
+            write!(f, "{}={}", address, value)?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct TraceParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TraceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "trace line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for TraceParseError {}
+
+impl FromStr for TraceEntry {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields, found {}", fields.len()));
+        }
+
+        let instr_ptr = fields[0]
+            .parse::<usize>()
+            .map_err(|_| format!("invalid pointer '{}'", fields[0]))?;
+        let word = fields[2]
+            .parse::<i128>()
+            .map_err(|_| format!("invalid instruction word '{}'", fields[2]))?;
+        let instruction = Instruction::decode(instr_ptr, word).map_err(|e| e.to_string())?;
+        if instruction.opcode.mnemonic() != fields[1] {
+            return Err(format!(
+                "mnemonic '{}' doesn't match instruction word {}",
+                fields[1], word
+            ));
+        }
+
+        let values: Vec<&str> = bracketed(fields[3])?
+            .split(',')
+            .filter(|o| !o.is_empty())
+            .collect();
+        let arg_count = instruction.opcode.arg_count();
+        if values.len() != arg_count {
+            return Err(format!(
+                "expected {} operands, found {}",
+                arg_count,
+                values.len()
+            ));
+        }
+        let mut operands = [None; 3];
+        for (operand, value) in operands.iter_mut().zip(values) {
+            *operand = match value {
+                "?" => None,
+                _ => Some(
+                    value
+                        .parse::<i128>()
+                        .map_err(|_| format!("invalid operand '{}'", value))?,
+                ),
+            };
+        }
+
+        let write = bracketed(fields[4])?;
+        let write = if write.is_empty() {
+            None
+        } else {
+            let mut parts = write.splitn(2, '=');
+            let address = parts.next().and_then(|a| a.parse::<usize>().ok());
+            let value = parts.next().and_then(|v| v.parse::<i128>().ok());
+            match (address, value) {
+                (Some(address), Some(value)) => Some((address, value)),
+                _ => return Err(format!("invalid write '{}'", write)),
+            }
+        };
+
+        Ok(TraceEntry {
+            instr_ptr,
+            instruction,
+            operands,
+            write,
+        })
+    }
+}
+
+fn bracketed(field: &str) -> Result<&str, String> {
+    field
+        .strip_prefix('[')
+        .and_then(|f| f.strip_suffix(']'))
+        .ok_or_else(|| format!("expected bracketed field, found '{}'", field))
+}
+
+/// Destination of the entries recorded by
+/// `IntcodeInterpreter::execute_traced`, called once per executed
+/// instruction.
+pub trait TraceSink {
+    fn record(&mut self, entry: &TraceEntry) -> io::Result<()>;
+}
+
+/// Collects entries in memory.
+impl TraceSink for Vec<TraceEntry> {
+    fn record(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        self.push(*entry);
+        Ok(())
+    }
+}
+
+/// Sink writing each entry as soon as it is recorded, in the format of
+/// `write_trace`.
+pub struct TraceWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(out: W) -> Self {
+        TraceWriter { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> TraceSink for TraceWriter<W> {
+    fn record(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        writeln!(self.out, "{}", entry)
+    }
+}
+
+/// Writes `trace` in line based text format.
+pub fn write_trace<W: Write>(trace: &[TraceEntry], out: &mut W) -> io::Result<()> {
+    for entry in trace {
+        writeln!(out, "{}", entry)?;
+    }
+    Ok(())
+}
+
+/// Reads trace written by `write_trace`. Empty lines are skipped.
+pub fn read_trace<R: BufRead>(input: R) -> Result<Vec<TraceEntry>, Box<dyn Error>> {
+    let mut trace = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = line
+            .parse::<TraceEntry>()
+            .map_err(|message| TraceParseError {
+                line: index + 1,
+                message,
+            })?;
+        trace.push(entry);
+    }
+    Ok(trace)
+}
+
+/// Returns index of the first entry where two traces differ, or `None`
+/// when they are identical.
+pub fn first_divergence(a: &[TraceEntry], b: &[TraceEntry]) -> Option<usize> {
+    match a.iter().zip(b).position(|(x, y)| x != y) {
+        Some(index) => Some(index),
+        None if a.len() != b.len() => Some(a.len().min(b.len())),
+        None => None,
+    }
+}
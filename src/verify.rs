@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::fs;
+
+use super::error::AocError;
+
+/// Result of comparing an answer with the expected one.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Verdict {
+    Correct,
+    Wrong {
+        expected: String,
+    },
+    /// No answer is known for the puzzle part.
+    Unknown,
+}
+
+/// Known puzzle answers, used to check solvers against a given input.
+///
+/// Each answer is written on its own line as `DD.P answer`, e.g.
+/// `01.2 5068210`. Answers spanning several lines continue on the
+/// following lines indented with a tab. Blank lines and lines starting
+/// with `#` are ignored. Trailing whitespace is not significant.
+#[derive(Clone, Debug, Default)]
+pub struct ExpectedAnswers {
+    answers: HashMap<(u32, u32), String>,
+}
+
+impl ExpectedAnswers {
+    pub fn from_file(file_name: &str) -> Result<Self, AocError> {
+        ExpectedAnswers::parse(&fs::read_to_string(file_name)?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AocError> {
+        let mut answers = HashMap::new();
+        let mut current: Option<(u32, u32)> = None;
+        for (number, line) in text.lines().enumerate() {
+            let error = |message: &str| {
+                AocError::InvalidInput(format!("answers line {}: {}", number + 1, message))
+            };
+
+            if let Some(continuation) = line.strip_prefix('\t') {
+                let key = current.ok_or_else(|| error("continuation without an answer"))?;
+                let answer: &mut String = answers.get_mut(&key).unwrap();
+                answer.push('\n');
+                answer.push_str(continuation.trim_end());
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                current = None;
+                continue;
+            }
+
+            let (puzzle, answer) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let key = puzzle
+                .split_once('.')
+                .and_then(|(day, part)| Some((day.parse().ok()?, part.parse().ok()?)))
+                .ok_or_else(|| error("expected puzzle as DD.P"))?;
+            if answers.insert(key, answer.trim().to_string()).is_some() {
+                return Err(error("duplicate answer"));
+            }
+            current = Some(key);
+        }
+
+        Ok(ExpectedAnswers { answers })
+    }
+
+    pub fn check(&self, day: u32, part: u32, answer: &str) -> Verdict {
+        let expected = match self.answers.get(&(day, part)) {
+            Some(expected) => expected,
+            None => return Verdict::Unknown,
+        };
+        if normalize(expected) == normalize(answer) {
+            Verdict::Correct
+        } else {
+            Verdict::Wrong {
+                expected: expected.clone(),
+            }
+        }
+    }
+}
+
+/// Lines of `answer` without trailing whitespace, leading and trailing
+/// empty lines.
+fn normalize(answer: &str) -> Vec<&str> {
+    let lines: Vec<&str> = answer.lines().map(str::trim_end).collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].to_vec(),
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::time::Duration;
+
+use aoc2019::bench::{self, Baseline, Phase};
+use aoc2019::interpreter::IntcodeInterpreter;
+use aoc2019::solver::{self, Parameters};
+
+#[test]
+fn timings_round_trip_through_baseline() {
+    let text = fs::read_to_string("./data/day01.txt").unwrap();
+    let puzzle = solver::solver(1).unwrap();
+    let timings = bench::time_solver(1, puzzle, &text, 3, &Parameters::default()).unwrap();
+    assert_eq!(timings.len(), 3);
+    for (timing, &phase) in timings.iter().zip(Phase::ALL.iter()) {
+        assert_eq!((timing.day, timing.phase, timing.runs), (1, phase, 3));
+        assert!(timing.min <= timing.median && timing.median <= timing.max);
+    }
+
+    let mut csv = Vec::new();
+    bench::write_csv(&timings, &mut csv).unwrap();
+    let baseline = Baseline::parse(&String::from_utf8(csv).unwrap()).unwrap();
+    for timing in &timings {
+        assert_eq!(baseline.median(1, timing.phase), Some(timing.median));
+    }
+    assert_eq!(baseline.median(2, Phase::Parse), None);
+}
+
+#[test]
+fn slowdowns_above_threshold_are_flagged() {
+    let baseline =
+        Baseline::parse("day,phase,runs,min_ns,median_ns,max_ns\n1,part1,1,100,100,100\n").unwrap();
+    let mut timing = bench::time_solver(
+        1,
+        solver::solver(1).unwrap(),
+        "12",
+        1,
+        &Parameters::default(),
+    )
+    .unwrap()
+    .remove(1);
+    timing.median = Duration::from_nanos(150);
+    assert_eq!(baseline.change(&timing), Some(50.0));
+    assert_eq!(
+        bench::slowdowns(&[timing.clone()], &baseline, 40.0).len(),
+        1
+    );
+    assert!(bench::slowdowns(&[timing], &baseline, 60.0).is_empty());
+
+    assert!(Baseline::parse("header\n1,part3,1,1,1,1\n").is_err());
+}
+
+#[test]
+fn intcode_timings_count_runs_and_steps() {
+    // reads a value and writes it back increased by one
+    let template = IntcodeInterpreter::new(&[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0]).unwrap();
+    let rate = bench::time_intcode("inc", &template, &[1], Duration::from_millis(1)).unwrap();
+    assert!(rate.runs > 0);
+    assert_eq!(rate.steps, 4 * rate.runs);
+    assert!(rate.elapsed >= Duration::from_millis(1));
+
+    let template = IntcodeInterpreter::from_file("./data/day02.txt").unwrap();
+    let search = bench::time_search(&template, Duration::from_millis(1)).unwrap();
+    assert!(search.searches > 0);
+
+    let mut table = Vec::new();
+    bench::write_intcode_table(&[rate], &search, &mut table).unwrap();
+    let table = String::from_utf8(table).unwrap();
+    let rows: Vec<&str> = table
+        .lines()
+        .map(|l| l.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(rows, ["workload", "inc", "workload", "day02"]);
+}
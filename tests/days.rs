@@ -0,0 +1,181 @@
+use aoc2019::error::AocError;
+use aoc2019::solver::{self, Parameters, Solution, SOLVERS};
+use aoc2019::verify::{ExpectedAnswers, Verdict};
+
+fn solve(day: u32, part: u32, parameters: &Parameters) -> Solution {
+    let puzzle = solver::solver(day).unwrap();
+    let input = puzzle
+        .read_input(&format!("./data/day{:02}.txt", day))
+        .unwrap();
+    puzzle.solve(&input, part, parameters).unwrap()
+}
+
+fn answer(day: u32, part: u32) -> String {
+    solve(day, part, &Parameters::default()).answer
+}
+
+#[test]
+fn day01() {
+    assert_eq!(answer(1, 1), "3380731");
+    assert_eq!(answer(1, 2), "5068210");
+}
+
+#[test]
+fn day02() {
+    assert_eq!(answer(2, 1), "4462686");
+    let solution = solve(2, 2, &Parameters::default());
+    assert_eq!(solution.answer, "5936");
+    assert_eq!(solution.message, "noun 59, verb 36, answer 5936");
+}
+
+#[test]
+fn day03() {
+    assert_eq!(answer(3, 1), "403");
+    assert_eq!(answer(3, 2), "4158");
+}
+
+#[test]
+fn day04() {
+    assert_eq!(answer(4, 1), "1686");
+    assert_eq!(answer(4, 2), "1145");
+    let parameters = Parameters {
+        range: Some((100000, 200000)),
+        ..Parameters::default()
+    };
+    assert_eq!(solve(4, 1, &parameters).answer, "1231");
+    assert_eq!(solve(4, 2, &parameters).answer, "898");
+}
+
+#[test]
+fn day04_ranges_up_to_largest_password() {
+    let puzzle = solver::solver(4).unwrap();
+    let input = puzzle.parse("4294967290-4294967295\n").unwrap();
+    let solution = puzzle.solve(&input, 1, &Parameters::default()).unwrap();
+    assert_eq!(solution.answer, "0");
+
+    assert!(puzzle.parse("5-3").is_err());
+    let parameters = Parameters {
+        range: Some((9, 1)),
+        ..Parameters::default()
+    };
+    let input = puzzle.parse("1-9").unwrap();
+    assert!(puzzle.solve(&input, 2, &parameters).is_err());
+}
+
+#[test]
+fn day05() {
+    assert_eq!(answer(5, 1), "12234644");
+    assert_eq!(answer(5, 2), "3508186");
+}
+
+#[test]
+fn day06() {
+    assert_eq!(answer(6, 1), "162439");
+    assert_eq!(answer(6, 2), "367");
+}
+
+#[test]
+fn day06_rejects_malformed_orbit_maps() {
+    let puzzle = solver::solver(6).unwrap();
+    for (orbits, message) in [
+        ("COM)A\nB)A\n", "A orbits both COM and B"),
+        ("COM)A\nA)COM\n", "orbits form a cycle through COM"),
+        ("COM)A\nB)C\n", "B doesn't orbit COM"),
+    ] {
+        let input = puzzle.parse(orbits).unwrap();
+        match puzzle.solve(&input, 1, &Parameters::default()) {
+            Err(AocError::InvalidInput(error)) => assert_eq!(error, message),
+            result => panic!("{:?}: unexpected {:?}", orbits, result),
+        }
+    }
+    let input = puzzle.parse("A)B\nB)C\nC)A\nC)YOU\nX)SAN\n").unwrap();
+    assert!(puzzle.solve(&input, 2, &Parameters::default()).is_err());
+}
+
+#[test]
+fn day07_with_any_number_of_workers() {
+    for workers in [1, 4] {
+        let parameters = Parameters {
+            workers,
+            ..Parameters::default()
+        };
+        let solution = solve(7, 1, &parameters);
+        assert_eq!(solution.answer, "273814");
+        assert!(solution.message.ends_with("[2, 0, 4, 3, 1]"));
+        let solution = solve(7, 2, &parameters);
+        assert_eq!(solution.answer, "34579864");
+        assert!(solution.message.ends_with("[6, 5, 9, 7, 8]"));
+    }
+}
+
+#[test]
+fn day08() {
+    assert_eq!(answer(8, 1), "1088");
+    let image = answer(8, 2);
+    let rows: Vec<&str> = image.lines().map(str::trim_end).collect();
+    assert_eq!(
+        rows,
+        vec![
+            "X     XX  X   XX  X XXX",
+            "X    X  X X   XX  X X  X",
+            "X    X     X X XXXX XXX",
+            "X    X XX   X  X  X X  X",
+            "X    X  X   X  X  X X  X",
+            "XXXX  XXX   X  X  X XXX",
+        ]
+    );
+    assert!(image.lines().all(|row| row.len() == 25));
+}
+
+#[test]
+fn registry_matches_answers_file() {
+    let answers = ExpectedAnswers::from_file("./data/answers.txt").unwrap();
+    for day in 1..=SOLVERS.len() as u32 {
+        for part in 1..=2 {
+            let answer = answer(day, part);
+            assert_eq!(answers.check(day, part, &answer), Verdict::Correct);
+        }
+    }
+    assert!(solver::solver(0).is_none());
+    assert!(solver::solver(SOLVERS.len() as u32 + 1).is_none());
+}
+
+#[test]
+fn missing_input_is_an_error() {
+    for puzzle in SOLVERS.iter() {
+        assert!(puzzle.read_input("./data/missing.txt").is_err());
+    }
+}
+
+#[test]
+fn invalid_input_is_an_error() {
+    for puzzle in SOLVERS.iter() {
+        assert!(puzzle.parse("not a puzzle input").is_err());
+    }
+}
+
+#[test]
+fn input_of_another_day_is_an_error() {
+    let input = solver::solver(1).unwrap().parse("12\n14\n").unwrap();
+    let parameters = Parameters::default();
+    match solver::solver(2).unwrap().solve(&input, 1, &parameters) {
+        Err(AocError::InvalidInput(_)) => {}
+        result => panic!("unexpected {:?}", result),
+    }
+}
+
+#[test]
+fn answers_are_checked_line_by_line() {
+    let answers = ExpectedAnswers::parse("# comment\n01.1 12\n08.2\n\t X  X\n\tXXXX\n").unwrap();
+    assert_eq!(answers.check(1, 1, "12"), Verdict::Correct);
+    assert_eq!(
+        answers.check(1, 1, "13"),
+        Verdict::Wrong {
+            expected: String::from("12")
+        }
+    );
+    assert_eq!(answers.check(1, 2, "12"), Verdict::Unknown);
+    assert_eq!(answers.check(8, 2, " X  X   \nXXXX\n"), Verdict::Correct);
+    assert!(ExpectedAnswers::parse("1 12").is_err());
+    assert!(ExpectedAnswers::parse("\tX").is_err());
+}
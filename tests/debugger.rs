@@ -0,0 +1,101 @@
+use std::env;
+use std::fs;
+use std::process;
+
+use aoc2019::debugger::Debugger;
+use aoc2019::interpreter::IntcodeInterpreter;
+
+// reads a value and writes it back increased by one
+const INCREMENT: &[i128] = &[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0];
+
+const TRANSCRIPT: &str = "\
+(icd) # stop on the input write, then on the add
+(icd) break 2
+breakpoint at 2
+(icd) watch 9
+watchpoint at 9
+(icd) breaks
+breakpoints: [2]
+watchpoints: [9]
+(icd) input 41
+queued 1 input value(s)
+(icd) continue
+state: watchpoint [9] <- 41
+(icd) continue
+=>    2: ADD [9], #1, [9]
+state: breakpoint at 2
+(icd) save SNAPSHOT
+snapshot saved to SNAPSHOT
+(icd) step
+=>    2: ADD [9], #1, [9]    ; [9] <- 42
+(icd) mem 9
+    9: 42
+(icd) set 9 100
+[9] <- 100
+(icd) continue
+state: finished
+(icd) output
+output: [100]
+(icd) load SNAPSHOT
+snapshot loaded from SNAPSHOT
+(icd) regs
+pc: 2, rb: 0, steps: 1, state: breakpoint at 2
+(icd) mem 9
+    9: 41
+(icd) step 2
+=>    2: ADD [9], #1, [9]    ; [9] <- 42
+=>    6: OUT [9]
+(icd) output
+output: [42]
+(icd) list 0 2
+      0: IN [9]
+      2: ADD [9], #1, [9]
+(icd) list 0 4611686018427387904
+error: count 4611686018427387904 exceeds limit of 4096
+(icd) bogus
+error: unknown command 'bogus', try 'help'
+(icd) quit
+";
+
+#[test]
+fn scripted_session_replays_transcript() {
+    let snapshot = env::temp_dir().join(format!("debugger-test-{}.snapshot", process::id()));
+    let snapshot = snapshot.to_str().unwrap();
+    let script = "\
+# stop on the input write, then on the add
+break 2
+watch 9
+breaks
+input 41
+continue
+continue
+save SNAPSHOT
+step
+mem 9
+set 9 100
+continue
+output
+load SNAPSHOT
+regs
+mem 9
+step 2
+output
+list 0 2
+list 0 4611686018427387904
+bogus
+quit
+step
+"
+    .replace("SNAPSHOT", snapshot);
+
+    let mut debugger = Debugger::new(IntcodeInterpreter::new(INCREMENT).unwrap());
+    let mut transcript = Vec::new();
+    debugger
+        .run(script.as_bytes(), &mut transcript, true)
+        .unwrap();
+    let _ = fs::remove_file(snapshot);
+    let transcript = String::from_utf8(transcript)
+        .unwrap()
+        .replace(snapshot, "SNAPSHOT");
+    assert_eq!(transcript, TRANSCRIPT);
+}
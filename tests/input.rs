@@ -0,0 +1,76 @@
+use aoc2019::input::{self, ParseError};
+
+fn location(error: ParseError) -> (usize, usize) {
+    (error.line, error.column)
+}
+
+#[test]
+fn parsers_tolerate_trailing_whitespace() {
+    assert_eq!(
+        input::comma_separated::<i64>("1, 2,3 \n\n"),
+        Ok(vec![1, 2, 3])
+    );
+    assert_eq!(
+        input::integer_lines::<i32>(" 12\n14 \n\n"),
+        Ok(vec![12, 14])
+    );
+    assert_eq!(
+        input::token_lines("a-b\nc\n", '-', |token| Ok::<_, String>(token.to_string())),
+        Ok(vec![
+            vec![String::from("a"), String::from("b")],
+            vec![String::from("c")]
+        ])
+    );
+    assert_eq!(
+        input::pairs("COM)B\r\nB)C\n", ')'),
+        Ok(vec![("COM", "B"), ("B", "C")])
+    );
+    assert_eq!(
+        input::digit_grid("012\n345\n", 3),
+        Ok(vec![vec![0, 1, 2], vec![3, 4, 5]])
+    );
+    assert_eq!(
+        input::digit_grid("012345", 3),
+        Ok(vec![vec![0, 1, 2], vec![3, 4, 5]])
+    );
+}
+
+#[test]
+fn errors_point_at_offending_token() {
+    let error = input::comma_separated::<i64>("1,2,\n 3,x\n").unwrap_err();
+    assert_eq!(location(error.clone()), (2, 4));
+    assert_eq!(error.message, "invalid integer 'x'");
+    assert_eq!(
+        location(input::integer_lines::<i32>("1\n2\n\n4\n").unwrap_err()),
+        (3, 1)
+    );
+    let error = input::token_lines("R1,U2\nD3,Q4", ',', |token| {
+        if token.starts_with('Q') {
+            Err(format!("bad step '{}'", token))
+        } else {
+            Ok(())
+        }
+    })
+    .unwrap_err();
+    assert_eq!(location(error.clone()), (2, 4));
+    assert_eq!(error.message, "bad step 'Q4'");
+    assert_eq!(location(input::pairs("A)B\nC\n", ')').unwrap_err()), (2, 1));
+    assert_eq!(
+        location(input::digit_grid("01\n2é", 2).unwrap_err()),
+        (2, 2)
+    );
+    assert_eq!(
+        location(input::digit_grid("012\n34", 3).unwrap_err()),
+        (2, 3)
+    );
+}
+
+#[test]
+fn empty_input_is_an_error() {
+    for text in ["", " \n\n"] {
+        assert!(input::comma_separated::<i64>(text).is_err());
+        assert!(input::integer_lines::<i64>(text).is_err());
+        assert!(input::pairs(text, ')').is_err());
+        assert!(input::digit_grid(text, 1).is_err());
+    }
+}
@@ -0,0 +1,389 @@
+use aoc2019::assembler::assemble;
+use aoc2019::disassembler::listing;
+use aoc2019::intcode_io::{AsciiOutput, CallbackIo};
+use aoc2019::interpreter::{
+    parse_program, read_program, BreakCause, ExecutionState, IntcodeError, IntcodeInterpreter,
+    DEFAULT_MEMORY_LIMIT,
+};
+use aoc2019::network::{Network, NetworkState};
+use aoc2019::trace::{first_divergence, read_trace, TraceParseError, TraceWriter};
+
+// reads a value and writes it back increased by one
+const INCREMENT: &[i128] = &[3, 9, 1001, 9, 1, 9, 4, 9, 99, 0];
+
+#[test]
+fn suspends_until_input_is_available() {
+    let mut machine = IntcodeInterpreter::new(INCREMENT).unwrap();
+    assert_eq!(machine.execute().unwrap(), ExecutionState::Suspended);
+    machine.set_input(41);
+    assert_eq!(machine.execute().unwrap(), ExecutionState::Finished);
+    assert_eq!(machine.get_output(), Some(42));
+    assert_eq!(machine.get_output(), None);
+}
+
+#[test]
+fn clones_run_independently() {
+    let template = IntcodeInterpreter::new(INCREMENT).unwrap();
+    let mut first = template.clone();
+    let mut second = template.clone();
+    first.set_input(1);
+    second.set_input(100);
+    first.execute().unwrap();
+    second.execute().unwrap();
+    assert_eq!(first.read_memory(9), Some(2));
+    assert_eq!(second.read_memory(9), Some(101));
+    assert_eq!(template.read_memory(9), Some(0));
+}
+
+#[test]
+fn clones_keep_writes_past_the_image_apart() {
+    const HIGH: usize = DEFAULT_MEMORY_LIMIT - 1;
+    let mut template = IntcodeInterpreter::new(INCREMENT).unwrap();
+    template.set_memory(HIGH, 3).unwrap();
+    let mut fork = template.clone();
+    fork.set_memory(HIGH, 4).unwrap();
+    fork.set_memory(HIGH - 1, 5).unwrap();
+    fork.set_memory(9, 6).unwrap();
+    assert_eq!(template.read_memory(HIGH), Some(3));
+    assert_eq!(template.read_memory(HIGH - 1), Some(0));
+    assert_eq!(template.read_memory(9), Some(0));
+    assert_eq!(fork.read_memory(HIGH), Some(4));
+    assert_eq!(fork.read_memory(HIGH - 1), Some(5));
+    assert_eq!(fork.read_memory(9), Some(6));
+}
+
+#[test]
+fn memory_grows_up_to_limit() {
+    let mut machine = IntcodeInterpreter::new(INCREMENT).unwrap();
+    machine.set_memory_limit(1000);
+    machine.set_memory(999, 7).unwrap();
+    assert_eq!(machine.read_memory(999), Some(7));
+    assert_eq!(machine.read_memory(500), Some(0));
+    assert_eq!(machine.read_memory(1000), None);
+    assert!(machine.set_memory(1000, 7).is_err());
+}
+
+#[test]
+fn stops_at_breakpoints_and_watchpoints() {
+    let mut machine = IntcodeInterpreter::new(INCREMENT).unwrap();
+    machine.set_input(5);
+    machine.add_breakpoint(6);
+    machine.add_watchpoint(9);
+    assert_eq!(
+        machine.execute().unwrap(),
+        ExecutionState::Breakpoint(BreakCause::Watch {
+            address: 9,
+            value: 5
+        })
+    );
+    assert_eq!(
+        machine.execute().unwrap(),
+        ExecutionState::Breakpoint(BreakCause::Watch {
+            address: 9,
+            value: 6
+        })
+    );
+    assert_eq!(
+        machine.execute().unwrap(),
+        ExecutionState::Breakpoint(BreakCause::Address(6))
+    );
+    assert_eq!(machine.execute().unwrap(), ExecutionState::Finished);
+}
+
+#[test]
+fn loads_programs_spread_over_lines() {
+    let program = "3,9,1001,9,\n  1,9,4,9 \n\n99,0,\n";
+    assert_eq!(parse_program(program).unwrap(), INCREMENT);
+    assert_eq!(parse_program("99\n").unwrap(), [99]);
+}
+
+#[test]
+fn rejects_empty_and_malformed_programs() {
+    for (program, line, column, message) in [
+        ("", 1, 1, "input is empty"),
+        ("\n \n", 1, 1, "input is empty"),
+        ("1,0,0,0\n99,x3", 2, 4, "invalid integer 'x3'"),
+        ("1,,0", 1, 3, "missing integer"),
+    ] {
+        match parse_program(program) {
+            Err(IntcodeError::InvalidProgram(e)) => {
+                assert_eq!(
+                    (e.line, e.column, e.message.as_str()),
+                    (line, column, message)
+                )
+            }
+            result => panic!("{:?}: unexpected {:?}", program, result),
+        }
+    }
+}
+
+/// Runs `program` to completion with `inputs` and collects its output.
+fn run_program(program: &[i128], inputs: &[i128]) -> Result<Vec<i128>, IntcodeError> {
+    let mut machine = IntcodeInterpreter::new(program)?;
+    for &input in inputs {
+        machine.set_input(input);
+    }
+    assert_eq!(machine.execute()?, ExecutionState::Finished);
+    Ok(std::iter::from_fn(|| machine.get_output()).collect())
+}
+
+#[test]
+fn quine_outputs_itself() {
+    let quine = [
+        109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+    ];
+    assert_eq!(run_program(&quine, &[]).unwrap(), quine);
+}
+
+#[test]
+fn writes_relative_to_base() {
+    // rb = 10; input to [rb-3], echoed back from there
+    assert_eq!(
+        run_program(&[109, 10, 203, -3, 204, -3, 99], &[17]).unwrap(),
+        [17]
+    );
+    // rb = 5; [rb+5] = 3 + 4, then output [rb+5]
+    let mut machine = IntcodeInterpreter::new(&[109, 5, 21101, 3, 4, 5, 204, 5, 99]).unwrap();
+    machine.execute().unwrap();
+    assert_eq!(machine.read_memory(10), Some(7));
+    assert_eq!(machine.get_output(), Some(7));
+}
+
+#[test]
+fn handles_values_beyond_i32() {
+    assert_eq!(
+        run_program(&[104, 1125899906842624, 99], &[]).unwrap(),
+        [1125899906842624]
+    );
+    assert_eq!(
+        run_program(INCREMENT, &[3_000_000_000]).unwrap(),
+        [3_000_000_001]
+    );
+}
+
+#[test]
+fn rejects_negative_relative_address() {
+    match run_program(&[204, -1, 99], &[]) {
+        Err(IntcodeError::InvalidAddress {
+            instr_ptr,
+            opcode,
+            address,
+        }) => assert_eq!((instr_ptr, opcode, address), (0, 204, -1)),
+        result => panic!("unexpected {:?}", result),
+    }
+}
+
+#[test]
+fn reports_unknown_opcode() {
+    let mut machine = IntcodeInterpreter::new(&[1, 0, 0, 0, 42]).unwrap();
+    let error = machine.execute().unwrap_err();
+    assert_eq!(error.to_string(), "[pc:4] unknown opcode 42");
+}
+
+#[test]
+fn snapshot_round_trip() {
+    let mut machine = IntcodeInterpreter::from_file("./data/day05.txt").unwrap();
+    machine.set_input(5);
+    for _ in 0..10 {
+        machine.step().unwrap();
+    }
+    let mut snapshot = Vec::new();
+    machine.save_snapshot(&mut snapshot).unwrap();
+
+    let mut restored = IntcodeInterpreter::load_snapshot(&snapshot[..]).unwrap();
+    machine.execute().unwrap();
+    restored.execute().unwrap();
+    assert_eq!(
+        restored.get_instruction_count(),
+        machine.get_instruction_count()
+    );
+    assert_eq!(restored.get_output(), machine.get_output());
+}
+
+/// Snapshot of `INCREMENT` with the line starting with `prefix` replaced
+/// by `replacement`, or removed if there is none.
+fn edited_snapshot(prefix: &str, replacement: Option<&str>) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    IntcodeInterpreter::new(INCREMENT)
+        .unwrap()
+        .save_snapshot(&mut snapshot)
+        .unwrap();
+    let snapshot = String::from_utf8(snapshot).unwrap();
+    let mut edited = String::new();
+    for line in snapshot.lines() {
+        match (line.starts_with(prefix), replacement) {
+            (false, _) => edited.push_str(line),
+            (true, Some(replacement)) => edited.push_str(replacement),
+            (true, None) => continue,
+        }
+        edited.push('\n');
+    }
+    edited.into_bytes()
+}
+
+#[test]
+fn rejects_incompatible_snapshot_version() {
+    let snapshot = edited_snapshot("intcode-snapshot", Some("intcode-snapshot 2"));
+    match IntcodeInterpreter::load_snapshot(&snapshot[..]) {
+        Err(IntcodeError::IncompatibleSnapshot { version }) => assert_eq!(version, "2"),
+        result => panic!("unexpected {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn reports_line_of_malformed_snapshot() {
+    let cases = [
+        (
+            "intcode-snapshot",
+            Some("snapshot 1"),
+            Some(1),
+            "missing snapshot header",
+        ),
+        (
+            "instr_ptr",
+            Some("instr_ptr x"),
+            Some(3),
+            "invalid value of 'instr_ptr'",
+        ),
+        (
+            "state",
+            Some("state sleeping"),
+            Some(6),
+            "invalid value of 'state'",
+        ),
+        (
+            "memory ",
+            Some("memory 3,#9"),
+            Some(7),
+            "invalid value of 'memory'",
+        ),
+        ("relative_base", None, None, "missing field 'relative_base'"),
+    ];
+    for &(prefix, replacement, expected_line, expected_message) in cases.iter() {
+        let snapshot = edited_snapshot(prefix, replacement);
+        match IntcodeInterpreter::load_snapshot(&snapshot[..]) {
+            Err(IntcodeError::InvalidSnapshot { line, message }) => {
+                assert_eq!((line, message.as_str()), (expected_line, expected_message))
+            }
+            result => panic!("{}: unexpected {:?}", expected_message, result.map(|_| ())),
+        }
+    }
+}
+
+#[test]
+fn custom_io_device() {
+    let mut written = Vec::new();
+    let mut machine = IntcodeInterpreter::new(INCREMENT)
+        .unwrap()
+        .with_io(CallbackIo::new(|| Some(9), |value| written.push(value)));
+    assert_eq!(machine.execute().unwrap(), ExecutionState::Finished);
+    drop(machine);
+    assert_eq!(written, vec![10]);
+}
+
+#[test]
+fn ascii_input_and_output() {
+    // echoes one character, then writes a value outside of ASCII range
+    let program = assemble("IN [10]\nOUT [10]\nOUT #10\nOUT #1000\nHALT\ndata 0").unwrap();
+    let mut machine = IntcodeInterpreter::new(&program).unwrap();
+    machine.set_input_line("A");
+    machine.execute().unwrap();
+    assert_eq!(
+        machine.get_ascii_output(),
+        Some(AsciiOutput::Line(String::from("A")))
+    );
+    assert_eq!(machine.get_ascii_output(), Some(AsciiOutput::Value(1000)));
+}
+
+#[test]
+fn disassembly_reassembles_to_same_program() {
+    for day in ["day02", "day05", "day07"] {
+        let program = read_program(&format!("./data/{}.txt", day)).unwrap();
+        assert_eq!(assemble(&listing(&program)).unwrap(), program, "{}", day);
+    }
+}
+
+#[test]
+fn assembles_relative_base_operands() {
+    assert_eq!(
+        assemble("OUT [rb]\nOUT [rb+0]\nOUT [rb - 2]\nOUT [rbx]\nrbx: HALT").unwrap(),
+        [204, 0, 204, 0, 204, -2, 4, 8, 99]
+    );
+    for (source, line) in [("rb: HALT", 1), ("HALT\nOUT #rb", 2), ("data rb", 1)] {
+        let error = assemble(source).unwrap_err();
+        assert_eq!(
+            (error.line, error.message.as_str()),
+            (line, "'rb' is reserved for the relative base"),
+            "{:?}",
+            source
+        );
+    }
+}
+
+/// Text trace of day 5 run with a single `input`.
+fn day05_trace(input: i128) -> String {
+    let mut machine = IntcodeInterpreter::from_file("./data/day05.txt").unwrap();
+    machine.set_input(input);
+    let mut sink = TraceWriter::new(Vec::new());
+    machine.execute_traced(&mut sink).unwrap();
+    String::from_utf8(sink.into_inner()).unwrap()
+}
+
+#[test]
+fn identical_runs_have_identical_traces() {
+    let text = day05_trace(1);
+    assert_eq!(text.lines().nth(1), Some("2 ADD 1 [1,1100,6] [6=1101]"));
+
+    let first = read_trace(text.as_bytes()).unwrap();
+    let second = read_trace(day05_trace(1).as_bytes()).unwrap();
+    assert_eq!(first_divergence(&first, &second), None);
+
+    let other = read_trace(day05_trace(5).as_bytes()).unwrap();
+    assert_eq!(first_divergence(&first, &other), Some(0));
+}
+
+#[test]
+fn reports_line_of_malformed_trace_entry() {
+    let text = "0 IN 3 [225] [225=1]\n\n2 ADD 1 [1,x,6] [6=1101]\n";
+    let error = read_trace(text.as_bytes()).unwrap_err();
+    let error = error.downcast_ref::<TraceParseError>().unwrap();
+    assert_eq!(
+        (error.line, error.message.as_str()),
+        (3, "invalid operand 'x'")
+    );
+}
+
+#[test]
+fn network_runs_the_same_in_turns_and_in_parallel() {
+    let machine = IntcodeInterpreter::new(INCREMENT).unwrap();
+    for parallel in [false, true] {
+        let mut network = Network::chain(vec![machine.clone(); 3]);
+        network.set_input(0, 0);
+        let state = if parallel {
+            network.run_parallel()
+        } else {
+            network.run()
+        };
+        assert_eq!(state.unwrap(), NetworkState::Finished);
+        assert_eq!(network.get_output(), Some((2, 3)));
+    }
+}
+
+#[test]
+fn network_reports_deadlock() {
+    let machine = IntcodeInterpreter::new(INCREMENT).unwrap();
+    for parallel in [false, true] {
+        let mut network = Network::ring(vec![machine.clone(); 2]);
+        let state = if parallel {
+            network.run_parallel()
+        } else {
+            network.run()
+        };
+        assert_eq!(
+            state.unwrap(),
+            NetworkState::Deadlock {
+                waiting: vec![0, 1]
+            }
+        );
+    }
+}